@@ -19,6 +19,32 @@ fn perf_test_multi(q_num: usize, t_num: usize) {
     assert_eq!(reg.measure_mask(mask).get() & !mask, 0);
 }
 
+fn repeat_plain(reg: &mut QReg, circuit: &MultiOp) {
+    for _ in 0..1000 {
+        reg.apply(circuit);
+    }
+}
+
+fn repeat_compiled(reg: &mut QReg, circuit: &CompiledOp) {
+    for _ in 0..1000 {
+        reg.apply_compiled(circuit);
+    }
+}
+
+fn repeated(c: &mut Criterion) {
+    let circuit = op::qft(0b1111111111);
+    let compiled = circuit.compile(10);
+
+    c.bench_function("repeat_qft_qu10_plain", |b| {
+        let mut reg = QReg::new(10);
+        b.iter(|| repeat_plain(&mut reg, black_box(&circuit)))
+    });
+    c.bench_function("repeat_qft_qu10_compiled", |b| {
+        let mut reg = QReg::new(10);
+        b.iter(|| repeat_compiled(&mut reg, black_box(&compiled)))
+    });
+}
+
 fn performance(c: &mut Criterion) {
     for qu_num in [18, 19, 20] {
         c.bench_function(format!("evaluate_qu{qu_num}_single").as_str(), |b| {
@@ -32,5 +58,5 @@ fn performance(c: &mut Criterion) {
     }
 }
 
-criterion_group!(benches, performance);
+criterion_group!(benches, performance, repeated);
 criterion_main!(benches);
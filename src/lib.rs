@@ -9,6 +9,11 @@ mod threads;
 pub mod operator;
 pub mod register;
 
+/// Complex type of amplitudes.
+pub use math::types::C;
+/// Real type, used for probabilities and angles: [`f64`] by default, or [`f32`], if ```f32``` feature is enabled.
+pub use math::types::R;
+
 #[cfg(feature = "interpreter")]
 pub mod qasm;
 
@@ -18,7 +23,8 @@ pub mod prelude {
     pub use crate::qasm::{Ast, Int};
     pub use crate::{
         operator as op,
-        operator::{Applicable, MultiOp, SingleOp},
+        operator::{Applicable, CompiledOp, MultiOp, SingleOp},
         register::*,
+        C, R,
     };
 }
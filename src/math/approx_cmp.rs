@@ -2,13 +2,18 @@ use float_cmp::*;
 
 use super::types::*;
 
-const ULPS: i64 = 2;
+const ULPS: <R as Ulps>::U = 2;
 
 #[inline]
 pub fn approx_eq_real(x: R, y: R) -> bool {
     approx_eq!(R, x, y, ulps = ULPS)
 }
 
+#[inline]
+pub fn approx_eq_real_eps(x: R, y: R, eps: R) -> bool {
+    approx_eq!(R, x, y, epsilon = eps, ulps = ULPS)
+}
+
 #[inline]
 pub fn approx_real(x: &C) -> bool {
     approx_eq_real(x.im, 0.0)
@@ -19,6 +24,12 @@ pub fn approx_eq_complex(a: &C, b: &C) -> bool {
     approx_eq_real(a.re, b.re) && approx_eq_real(a.im, b.im)
 }
 
+#[inline]
+pub fn approx_eq_complex_eps(a: &C, b: &C, eps: R) -> bool {
+    approx_eq!(R, a.re, b.re, epsilon = eps, ulps = ULPS)
+        && approx_eq!(R, a.im, b.im, epsilon = eps, ulps = ULPS)
+}
+
 #[inline]
 pub fn approx_eq_conj(a: &C, b: &C) -> bool {
     approx_eq_real(a.re, b.re) && approx_eq_real(a.im, -b.im)
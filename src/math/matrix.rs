@@ -22,11 +22,17 @@ pub fn is_diagonal_m2(u: &M2) -> bool {
 }
 
 pub fn is_unitary_m1(u: &M1) -> bool {
+    is_unitary_m1_eps(u, 0.0)
+}
+
+pub fn is_unitary_m1_eps(u: &M1, eps: R) -> bool {
     let e00 = u[0b00].norm_sqr() + u[0b01].norm_sqr();
     let e11 = u[0b10].norm_sqr() + u[0b11].norm_sqr();
     let e01 = u[0b00] * u[0b10].conj() + u[0b01] * u[0b11].conj();
 
-    approx_eq_real(e00, 1.0) && approx_eq_real(e11, 1.0) && approx_eq_real(e01.re + e01.im, 0.0)
+    approx_eq_real_eps(e00, 1.0, eps)
+        && approx_eq_real_eps(e11, 1.0, eps)
+        && approx_eq_real_eps(e01.re + e01.im, 0.0, eps)
 }
 
 pub fn inverse_unitary_m1(u: &M1) -> M1 {
@@ -50,6 +56,10 @@ fn hermitian_mul(i: N, j: N, u: &M2) -> C {
 }
 
 pub fn is_unitary_m2(u: &M2) -> bool {
+    is_unitary_m2_eps(u, 0.0)
+}
+
+pub fn is_unitary_m2_eps(u: &M2, eps: R) -> bool {
     let e00 = hermitian_mul(0, 0, u).re;
     let e11 = hermitian_mul(1, 1, u).re;
     let e22 = hermitian_mul(2, 2, u).re;
@@ -61,16 +71,16 @@ pub fn is_unitary_m2(u: &M2) -> bool {
     let e13 = hermitian_mul(1, 3, u);
     let e23 = hermitian_mul(2, 3, u);
 
-    approx_eq_real(e00, 1.0)
-        && approx_eq_real(e11, 1.0)
-        && approx_eq_real(e22, 1.0)
-        && approx_eq_real(e33, 1.0)
-        && approx_eq_real(e01.re + e01.im, 0.0)
-        && approx_eq_real(e02.re + e02.im, 0.0)
-        && approx_eq_real(e03.re + e03.im, 0.0)
-        && approx_eq_real(e12.re + e12.im, 0.0)
-        && approx_eq_real(e13.re + e13.im, 0.0)
-        && approx_eq_real(e23.re + e23.im, 0.0)
+    approx_eq_real_eps(e00, 1.0, eps)
+        && approx_eq_real_eps(e11, 1.0, eps)
+        && approx_eq_real_eps(e22, 1.0, eps)
+        && approx_eq_real_eps(e33, 1.0, eps)
+        && approx_eq_real_eps(e01.re + e01.im, 0.0, eps)
+        && approx_eq_real_eps(e02.re + e02.im, 0.0, eps)
+        && approx_eq_real_eps(e03.re + e03.im, 0.0, eps)
+        && approx_eq_real_eps(e12.re + e12.im, 0.0, eps)
+        && approx_eq_real_eps(e13.re + e13.im, 0.0, eps)
+        && approx_eq_real_eps(e23.re + e23.im, 0.0, eps)
 }
 
 pub fn inverse_unitary_m2(u: &M2) -> M2 {
@@ -123,3 +133,52 @@ pub fn is_hermitian_m2(u: &M2) -> bool {
         && approx_eq_conj(&u[0b1011], &u[0b1110])
         && approx_real(&u[0b1111])
 }
+
+/// Singular values of the matrix, given by its columns.
+/// One-sided Jacobi method is used, so matrix is expected to have fewer columns, than rows.
+pub fn singular_values(mut cols: Vec<Vec<C>>) -> Vec<R> {
+    const EPS: R = 1e-15;
+    const MAX_SWEEPS: usize = 64;
+
+    let dot = |a: &[C], b: &[C]| -> C { a.iter().zip(b).map(|(a, b)| a.conj() * b).sum() };
+    let norm_sqr = |a: &[C]| -> R { a.iter().map(|a| a.norm_sqr()).sum() };
+
+    for _ in 0..MAX_SWEEPS {
+        let mut rotated = false;
+
+        for p in 0..cols.len() {
+            for q in (p + 1)..cols.len() {
+                let alpha = norm_sqr(&cols[p]);
+                let beta = norm_sqr(&cols[q]);
+                let (gamma, phase) = dot(&cols[p], &cols[q]).to_polar();
+
+                if gamma <= EPS * (alpha * beta).sqrt() || gamma <= EPS * EPS {
+                    continue;
+                }
+                rotated = true;
+
+                let zeta = (beta - alpha) / (2.0 * gamma);
+                let t = zeta.signum() / (zeta.abs() + (1.0 + zeta * zeta).sqrt());
+                let c = 1.0 / (1.0 + t * t).sqrt();
+                let s = c * t;
+                let phase = C::from_polar(1.0, -phase);
+
+                let (left, right) = cols.split_at_mut(q);
+                left[p]
+                    .iter_mut()
+                    .zip(right[0].iter_mut())
+                    .for_each(|(u, v)| {
+                        let (u_0, v_0) = (*u, *v * phase);
+                        *u = u_0 * c - v_0 * s;
+                        *v = u_0 * s + v_0 * c;
+                    });
+            }
+        }
+
+        if !rotated {
+            break;
+        }
+    }
+
+    cols.iter().map(|col| norm_sqr(col).sqrt()).collect()
+}
@@ -16,6 +16,15 @@ pub mod consts {
         C { re: 0., im: -1. },
     ];
 
+    /// Absolute tolerance for values, computed from user's input, e.g. unitarity of custom matrices.
+    #[cfg(not(feature = "f32"))]
+    pub const TOLERANCE: R = 1e-10;
+    #[cfg(feature = "f32")]
+    pub const TOLERANCE: R = 1e-5;
+
+    #[cfg(feature = "f32")]
+    pub use std::f32::consts::*;
+    #[cfg(not(feature = "f32"))]
     pub use std::f64::consts::*;
 }
 
@@ -23,13 +32,29 @@ pub mod types {
     pub type N = usize;
     pub type Z = isize;
 
+    #[cfg(not(feature = "f32"))]
     pub type R = f64;
+    #[cfg(feature = "f32")]
+    pub type R = f32;
     pub type C = num_complex::Complex<R>;
 
     pub type M1 = [C; 4];
     pub type M2 = [C; 16];
 }
 
+// Conversions for external libraries, which work only with f64
+#[inline]
+#[allow(clippy::unnecessary_cast)]
+pub fn to_f64(x: R) -> f64 {
+    x as f64
+}
+
+#[inline]
+#[allow(clippy::unnecessary_cast)]
+pub fn from_f64(x: f64) -> R {
+    x as R
+}
+
 use types::*;
 
 #[inline]
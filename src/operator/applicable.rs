@@ -1,6 +1,15 @@
 #![allow(clippy::uninit_vec)]
 
-use crate::math::types::*;
+use crate::{math::types::*, operator::SingleOp};
+
+/// Error channel, which is applied after each gate of noisy evaluation.
+///
+/// Implemented by [`NoiseModel`](crate::register::NoiseModel).
+#[doc(hidden)]
+pub trait Noise {
+    /// Apply error to the qubits from *act* mask of state *psi*.
+    fn apply(&self, act: N, psi: &mut [C]);
+}
 
 pub trait Applicable: Sized + Sync {
     fn apply(&self, psi_i: &[C], psi_o: &mut Vec<C>);
@@ -10,10 +19,31 @@ pub trait Applicable: Sized + Sync {
 
     fn act_on(&self) -> N;
 
+    #[doc(hidden)]
+    fn apply_noisy(&self, psi_i: &[C], psi_o: &mut Vec<C>, noise: &dyn Noise) {
+        self.apply(psi_i, psi_o);
+        noise.apply(self.act_on(), psi_o);
+    }
+
+    #[doc(hidden)]
+    #[cfg(feature = "multi-thread")]
+    fn apply_sync_noisy(&self, psi_i: &[C], psi_o: &mut Vec<C>, noise: &dyn Noise) {
+        self.apply_sync(psi_i, psi_o);
+        noise.apply(self.act_on(), psi_o);
+    }
+
     fn dgr(self) -> Self;
 
     fn c(self, c_mask: N) -> Option<Self>;
 
+    /// Sequence of gates, if operation consists only of [`SingleOp`]s.
+    #[doc(hidden)]
+    fn gates(&self) -> Option<Box<dyn Iterator<Item = &SingleOp> + '_>> {
+        None
+    }
+
+    /// Dense matrix of operation on *size* qubits, obtained by applying it to each basis state.
+    /// Matrix is indexed as ```matrix[row][column]```.
     fn matrix(&self, size: N) -> Vec<Vec<C>> {
         const O: C = C { re: 0.0, im: 0.0 };
         const I: C = C { re: 1.0, im: 0.0 };
@@ -0,0 +1,66 @@
+use super::*;
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct Op {
+    a_mask: N,
+}
+
+impl Op {
+    pub fn new(a_mask: N) -> Self {
+        Self { a_mask }
+    }
+}
+
+impl AtomicOp for Op {
+    fn atomic_op(&self, psi: &[C], idx: N) -> C {
+        psi[idx]
+    }
+
+    fn diagonal(&self, _: N) -> Option<C> {
+        Some(C_ONE)
+    }
+
+    fn name(&self) -> String {
+        format!("Barrier{}", self.a_mask)
+    }
+
+    fn qasm(&self) -> Option<String> {
+        Some("barrier".to_string())
+    }
+
+    fn acts_on(&self) -> N {
+        self.a_mask
+    }
+
+    fn remap(self, f: &dyn Fn(N) -> N) -> AtomicOpDispatch {
+        Self {
+            a_mask: f(self.a_mask),
+        }
+        .this()
+    }
+
+    fn this(self) -> dispatch::AtomicOpDispatch {
+        dispatch::AtomicOpDispatch::Barrier(self)
+    }
+
+    fn dgr(self) -> dispatch::AtomicOpDispatch {
+        dispatch::AtomicOpDispatch::Barrier(self)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn matrix_repr() {
+    use crate::operator::single::*;
+
+    const O: C = C { re: 0.0, im: 0.0 };
+    const I: C = C { re: 1.0, im: 0.0 };
+
+    let op: SingleOp = Op::new(0b11).into();
+    assert_eq!(op.name(), "Barrier3");
+    assert_eq!(op.act_on(), 0b11);
+    assert_eq!(
+        op.matrix(2),
+        [[I, O, O, O], [O, I, O, O], [O, O, I, O], [O, O, O, I]]
+    );
+}
@@ -5,6 +5,7 @@ use std::fmt;
 use super::*;
 
 type Id = id::Op;
+type Barrier = barrier::Op;
 type X = x::Op;
 type RX = rx::Op;
 type RXX = rxx::Op;
@@ -68,6 +69,21 @@ pub trait AtomicOp: Clone + PartialEq + Sync + Send {
 
     fn name(&self) -> String;
 
+    /// Name of gate in OpenQASM with parameters, if any.
+    fn qasm(&self) -> Option<String> {
+        None
+    }
+
+    /// Matrix of gate in row-major order, if gate acts on exactly one qubit.
+    fn matrix_m1(&self) -> Option<M1> {
+        None
+    }
+
+    /// Diagonal element of gate's matrix for basis state *idx*, if gate is diagonal.
+    fn diagonal(&self, _idx: N) -> Option<C> {
+        None
+    }
+
     fn is_valid(&self) -> bool {
         true
     }
@@ -76,6 +92,9 @@ pub trait AtomicOp: Clone + PartialEq + Sync + Send {
 
     fn this(self) -> AtomicOpDispatch;
 
+    /// Same gate with every qubit mask transformed by *f*.
+    fn remap(self, f: &dyn Fn(N) -> N) -> AtomicOpDispatch;
+
     fn dgr(self) -> AtomicOpDispatch;
 }
 
@@ -83,6 +102,7 @@ pub trait AtomicOp: Clone + PartialEq + Sync + Send {
 #[derive(Clone, PartialEq)]
 pub enum AtomicOpDispatch {
     Id,
+    Barrier,
     X,
     RX,
     RXX,
@@ -21,10 +21,22 @@ impl AtomicOp for Op {
         (psi.0 + psi.1).scale(FRAC_1_SQRT_2)
     }
 
+    fn matrix_m1(&self) -> Option<M1> {
+        if self.a_mask.count_ones() != 1 {
+            return None;
+        }
+        let h = C::from(FRAC_1_SQRT_2);
+        Some([h, h, h, -h])
+    }
+
     fn name(&self) -> String {
         format!("H{}", self.a_mask)
     }
 
+    fn qasm(&self) -> Option<String> {
+        Some("h".to_string())
+    }
+
     fn is_valid(&self) -> bool {
         self.a_mask.count_ones() == 1
     }
@@ -33,6 +45,13 @@ impl AtomicOp for Op {
         self.a_mask
     }
 
+    fn remap(self, f: &dyn Fn(N) -> N) -> AtomicOpDispatch {
+        Self {
+            a_mask: f(self.a_mask),
+        }
+        .this()
+    }
+
     fn this(self) -> AtomicOpDispatch {
         AtomicOpDispatch::H1(self)
     }
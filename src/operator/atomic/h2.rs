@@ -41,6 +41,10 @@ impl AtomicOp for Op {
         format!("H{}", self.a_mask | self.b_mask)
     }
 
+    fn qasm(&self) -> Option<String> {
+        Some("h".to_string())
+    }
+
     fn is_valid(&self) -> bool {
         self.a_mask.count_ones() == 1
             && self.b_mask.count_ones() == 1
@@ -51,6 +55,15 @@ impl AtomicOp for Op {
         self.ab_mask
     }
 
+    fn remap(self, f: &dyn Fn(N) -> N) -> AtomicOpDispatch {
+        Self {
+            a_mask: f(self.a_mask),
+            b_mask: f(self.b_mask),
+            ab_mask: f(self.ab_mask),
+        }
+        .this()
+    }
+
     fn this(self) -> AtomicOpDispatch {
         AtomicOpDispatch::H2(self)
     }
@@ -40,6 +40,14 @@ impl AtomicOp for Op {
         format!("iSWAP{}", self.ab_mask)
     }
 
+    fn qasm(&self) -> Option<String> {
+        if self.dagger {
+            None
+        } else {
+            Some("i_swap".to_string())
+        }
+    }
+
     fn is_valid(&self) -> bool {
         self.ab_mask.count_ones() == 2
     }
@@ -48,6 +56,14 @@ impl AtomicOp for Op {
         self.ab_mask
     }
 
+    fn remap(self, f: &dyn Fn(N) -> N) -> AtomicOpDispatch {
+        Self {
+            ab_mask: f(self.ab_mask),
+            ..self
+        }
+        .this()
+    }
+
     fn this(self) -> AtomicOpDispatch {
         AtomicOpDispatch::ISwap(self)
     }
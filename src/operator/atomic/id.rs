@@ -8,6 +8,10 @@ impl AtomicOp for Op {
         psi[idx]
     }
 
+    fn diagonal(&self, _: N) -> Option<C> {
+        Some(C_ONE)
+    }
+
     fn name(&self) -> String {
         "Id".to_string()
     }
@@ -16,6 +20,10 @@ impl AtomicOp for Op {
         0
     }
 
+    fn remap(self, _: &dyn Fn(N) -> N) -> AtomicOpDispatch {
+        self.this()
+    }
+
     fn this(self) -> dispatch::AtomicOpDispatch {
         dispatch::AtomicOpDispatch::Id(self)
     }
@@ -2,6 +2,7 @@
 
 use crate::math::{consts::*, types::*};
 
+pub mod barrier;
 pub mod id;
 
 pub mod rx;
@@ -28,10 +28,22 @@ impl AtomicOp for Op {
         }
     }
 
+    fn matrix_m1(&self) -> Option<M1> {
+        if self.a_mask.count_ones() != 1 {
+            return None;
+        }
+        let (cos, sin) = (C::from(self.phase.re), C::from(self.phase.im));
+        Some([cos, -sin, sin, cos])
+    }
+
     fn name(&self) -> String {
         format!("RY{}({})", self.a_mask, 2.0 * self.phase.arg())
     }
 
+    fn qasm(&self) -> Option<String> {
+        Some(format!("ry({})", 2.0 * self.phase.arg()))
+    }
+
     fn is_valid(&self) -> bool {
         self.a_mask.count_ones() == 1
     }
@@ -40,6 +52,14 @@ impl AtomicOp for Op {
         self.a_mask
     }
 
+    fn remap(self, f: &dyn Fn(N) -> N) -> AtomicOpDispatch {
+        Self {
+            a_mask: f(self.a_mask),
+            ..self
+        }
+        .this()
+    }
+
     fn this(self) -> AtomicOpDispatch {
         AtomicOpDispatch::RY(self)
     }
@@ -32,6 +32,10 @@ impl AtomicOp for Op {
         format!("RYY{}({})", self.ab_mask, 2.0 * self.phase.arg())
     }
 
+    fn qasm(&self) -> Option<String> {
+        Some(format!("ryy({})", 2.0 * self.phase.arg()))
+    }
+
     fn is_valid(&self) -> bool {
         self.ab_mask.count_ones() == 2
     }
@@ -40,6 +44,14 @@ impl AtomicOp for Op {
         self.ab_mask
     }
 
+    fn remap(self, f: &dyn Fn(N) -> N) -> AtomicOpDispatch {
+        Self {
+            ab_mask: f(self.ab_mask),
+            ..self
+        }
+        .this()
+    }
+
     fn this(self) -> AtomicOpDispatch {
         AtomicOpDispatch::RYY(self)
     }
@@ -24,10 +24,29 @@ impl AtomicOp for Op {
         phase * psi[idx]
     }
 
+    fn diagonal(&self, idx: N) -> Option<C> {
+        let mut phase = self.phase;
+        if idx & self.a_mask == 0 {
+            phase.im = -phase.im;
+        }
+        Some(phase)
+    }
+
+    fn matrix_m1(&self) -> Option<M1> {
+        if self.a_mask.count_ones() != 1 {
+            return None;
+        }
+        Some([self.phase.conj(), C_ZERO, C_ZERO, self.phase])
+    }
+
     fn name(&self) -> String {
         format!("RZ{}({})", self.a_mask, 2.0 * self.phase.arg())
     }
 
+    fn qasm(&self) -> Option<String> {
+        Some(format!("rz({})", 2.0 * self.phase.arg()))
+    }
+
     fn is_valid(&self) -> bool {
         self.a_mask.count_ones() == 1
     }
@@ -36,6 +55,14 @@ impl AtomicOp for Op {
         self.a_mask
     }
 
+    fn remap(self, f: &dyn Fn(N) -> N) -> AtomicOpDispatch {
+        Self {
+            a_mask: f(self.a_mask),
+            ..self
+        }
+        .this()
+    }
+
     fn this(self) -> AtomicOpDispatch {
         AtomicOpDispatch::RZ(self)
     }
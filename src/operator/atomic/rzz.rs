@@ -24,10 +24,22 @@ impl AtomicOp for Op {
         phase * psi[idx]
     }
 
+    fn diagonal(&self, idx: N) -> Option<C> {
+        let mut phase = self.phase;
+        if (idx & self.ab_mask).count_ones() & 1 == 0 {
+            phase.im = -phase.im;
+        }
+        Some(phase)
+    }
+
     fn name(&self) -> String {
         format!("RZZ{}({})", self.ab_mask, 2.0 * self.phase.arg())
     }
 
+    fn qasm(&self) -> Option<String> {
+        Some(format!("rzz({})", 2.0 * self.phase.arg()))
+    }
+
     fn is_valid(&self) -> bool {
         self.ab_mask.count_ones() == 2
     }
@@ -36,6 +48,14 @@ impl AtomicOp for Op {
         self.ab_mask
     }
 
+    fn remap(self, f: &dyn Fn(N) -> N) -> AtomicOpDispatch {
+        Self {
+            ab_mask: f(self.ab_mask),
+            ..self
+        }
+        .this()
+    }
+
     fn this(self) -> AtomicOpDispatch {
         AtomicOpDispatch::RZZ(self)
     }
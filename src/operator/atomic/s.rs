@@ -24,14 +24,42 @@ impl AtomicOp for Op {
         crate::math::rotate(psi[idx], count)
     }
 
+    fn diagonal(&self, idx: N) -> Option<C> {
+        let mut count = (idx & self.a_mask).count_ones() as usize;
+        if self.dagger {
+            count = (!count).wrapping_add(1);
+        }
+        Some(crate::math::rotate(C_ONE, count))
+    }
+
+    fn matrix_m1(&self) -> Option<M1> {
+        if self.a_mask.count_ones() != 1 {
+            return None;
+        }
+        let phase = if self.dagger { -C_IMAG } else { C_IMAG };
+        Some([C_ONE, C_ZERO, C_ZERO, phase])
+    }
+
     fn name(&self) -> String {
         format!("S{}", self.a_mask)
     }
 
+    fn qasm(&self) -> Option<String> {
+        Some(if self.dagger { "sdg" } else { "s" }.to_string())
+    }
+
     fn acts_on(&self) -> N {
         self.a_mask
     }
 
+    fn remap(self, f: &dyn Fn(N) -> N) -> AtomicOpDispatch {
+        Self {
+            a_mask: f(self.a_mask),
+            ..self
+        }
+        .this()
+    }
+
     fn this(self) -> AtomicOpDispatch {
         AtomicOpDispatch::S(self)
     }
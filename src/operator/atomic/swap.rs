@@ -25,6 +25,10 @@ impl AtomicOp for Op {
         format!("SWAP{}", self.ab_mask)
     }
 
+    fn qasm(&self) -> Option<String> {
+        Some("swap".to_string())
+    }
+
     fn is_valid(&self) -> bool {
         self.ab_mask.count_ones() == 2
     }
@@ -33,6 +37,13 @@ impl AtomicOp for Op {
         self.ab_mask
     }
 
+    fn remap(self, f: &dyn Fn(N) -> N) -> AtomicOpDispatch {
+        Self {
+            ab_mask: f(self.ab_mask),
+        }
+        .this()
+    }
+
     fn this(self) -> AtomicOpDispatch {
         AtomicOpDispatch::Swap(self)
     }
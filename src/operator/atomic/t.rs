@@ -34,14 +34,51 @@ impl AtomicOp for Op {
         }
     }
 
+    fn diagonal(&self, idx: N) -> Option<C> {
+        let mut count = (idx & self.a_mask).count_ones() as usize;
+        if self.dagger {
+            count = (!count).wrapping_add(1);
+        }
+        let phase = crate::math::rotate(C_ONE, count >> 1);
+        if count & 1 == 1 {
+            Some(EXP_I_PI_4 * phase)
+        } else {
+            Some(phase)
+        }
+    }
+
+    fn matrix_m1(&self) -> Option<M1> {
+        if self.a_mask.count_ones() != 1 {
+            return None;
+        }
+        let phase = if self.dagger {
+            EXP_I_PI_4.conj()
+        } else {
+            EXP_I_PI_4
+        };
+        Some([C_ONE, C_ZERO, C_ZERO, phase])
+    }
+
     fn name(&self) -> String {
         format!("T{}", self.a_mask)
     }
 
+    fn qasm(&self) -> Option<String> {
+        Some(if self.dagger { "tdg" } else { "t" }.to_string())
+    }
+
     fn acts_on(&self) -> N {
         self.a_mask
     }
 
+    fn remap(self, f: &dyn Fn(N) -> N) -> AtomicOpDispatch {
+        Self {
+            a_mask: f(self.a_mask),
+            ..self
+        }
+        .this()
+    }
+
     fn this(self) -> dispatch::AtomicOpDispatch {
         dispatch::AtomicOpDispatch::T(self)
     }
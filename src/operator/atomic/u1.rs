@@ -1,5 +1,5 @@
 use super::*;
-use crate::math::matrix::{inverse_unitary_m1, is_unitary_m1};
+use crate::math::matrix::{inverse_unitary_m1, is_unitary_m1_eps};
 
 #[derive(Clone, Copy, PartialEq)]
 pub struct Op {
@@ -11,6 +11,10 @@ impl Op {
     pub fn new(a_mask: N, matrix: M1) -> Self {
         Self { a_mask, matrix }
     }
+
+    pub fn is_valid_eps(&self, eps: R) -> bool {
+        self.a_mask.count_ones() == 1 && is_unitary_m1_eps(&self.matrix, eps)
+    }
 }
 
 impl AtomicOp for Op {
@@ -24,6 +28,10 @@ impl AtomicOp for Op {
         }
     }
 
+    fn matrix_m1(&self) -> Option<M1> {
+        Some(self.matrix)
+    }
+
     fn name(&self) -> String {
         format!(
             "U{}{:?}",
@@ -33,13 +41,21 @@ impl AtomicOp for Op {
     }
 
     fn is_valid(&self) -> bool {
-        self.a_mask.count_ones() == 1 && is_unitary_m1(&self.matrix)
+        self.is_valid_eps(0.0)
     }
 
     fn acts_on(&self) -> N {
         self.a_mask
     }
 
+    fn remap(self, f: &dyn Fn(N) -> N) -> AtomicOpDispatch {
+        Self {
+            a_mask: f(self.a_mask),
+            ..self
+        }
+        .this()
+    }
+
     fn this(self) -> dispatch::AtomicOpDispatch {
         dispatch::AtomicOpDispatch::U1(self)
     }
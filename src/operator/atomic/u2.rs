@@ -1,5 +1,5 @@
 use super::*;
-use crate::math::matrix::{inverse_unitary_m2, is_unitary_m2};
+use crate::math::matrix::{inverse_unitary_m2, is_unitary_m2_eps};
 
 #[derive(Clone, Copy, PartialEq)]
 pub struct Op {
@@ -16,6 +16,12 @@ impl Op {
             matrix,
         }
     }
+
+    pub fn is_valid_eps(&self, eps: R) -> bool {
+        self.a_mask.count_ones() == 1
+            && self.b_mask.count_ones() == 1
+            && is_unitary_m2_eps(&self.matrix, eps)
+    }
 }
 
 impl AtomicOp for Op {
@@ -62,13 +68,20 @@ impl AtomicOp for Op {
     }
 
     fn is_valid(&self) -> bool {
-        self.a_mask.count_ones() == 1
-            && self.b_mask.count_ones() == 1
-            && is_unitary_m2(&self.matrix)
+        self.is_valid_eps(0.0)
     }
 
     fn acts_on(&self) -> N {
-        self.a_mask
+        self.a_mask | self.b_mask
+    }
+
+    fn remap(self, f: &dyn Fn(N) -> N) -> AtomicOpDispatch {
+        Self {
+            a_mask: f(self.a_mask),
+            b_mask: f(self.b_mask),
+            ..self
+        }
+        .this()
     }
 
     fn this(self) -> dispatch::AtomicOpDispatch {
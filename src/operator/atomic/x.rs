@@ -16,14 +16,32 @@ impl AtomicOp for Op {
         psi[idx ^ self.a_mask]
     }
 
+    fn matrix_m1(&self) -> Option<M1> {
+        if self.a_mask.count_ones() != 1 {
+            return None;
+        }
+        Some([C_ZERO, C_ONE, C_ONE, C_ZERO])
+    }
+
     fn name(&self) -> String {
         format!("X{}", self.a_mask)
     }
 
+    fn qasm(&self) -> Option<String> {
+        Some("x".to_string())
+    }
+
     fn acts_on(&self) -> N {
         self.a_mask
     }
 
+    fn remap(self, f: &dyn Fn(N) -> N) -> AtomicOpDispatch {
+        Self {
+            a_mask: f(self.a_mask),
+        }
+        .this()
+    }
+
     fn this(self) -> dispatch::AtomicOpDispatch {
         dispatch::AtomicOpDispatch::X(self)
     }
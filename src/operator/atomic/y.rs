@@ -22,14 +22,33 @@ impl AtomicOp for Op {
         crate::math::rotate(psi[idx ^ self.a_mask], i_pow)
     }
 
+    fn matrix_m1(&self) -> Option<M1> {
+        if self.a_mask.count_ones() != 1 {
+            return None;
+        }
+        Some([C_ZERO, -C_IMAG, C_IMAG, C_ZERO])
+    }
+
     fn name(&self) -> String {
         format!("Y{}", self.a_mask)
     }
 
+    fn qasm(&self) -> Option<String> {
+        Some("y".to_string())
+    }
+
     fn acts_on(&self) -> N {
         self.a_mask
     }
 
+    fn remap(self, f: &dyn Fn(N) -> N) -> AtomicOpDispatch {
+        Self {
+            a_mask: f(self.a_mask),
+            ..self
+        }
+        .this()
+    }
+
     fn this(self) -> dispatch::AtomicOpDispatch {
         dispatch::AtomicOpDispatch::Y(self)
     }
@@ -20,14 +20,40 @@ impl AtomicOp for Op {
         }
     }
 
+    fn diagonal(&self, idx: N) -> Option<C> {
+        if (idx & self.a_mask).count_ones() & 1 == 1 {
+            Some(-C_ONE)
+        } else {
+            Some(C_ONE)
+        }
+    }
+
+    fn matrix_m1(&self) -> Option<M1> {
+        if self.a_mask.count_ones() != 1 {
+            return None;
+        }
+        Some([C_ONE, C_ZERO, C_ZERO, -C_ONE])
+    }
+
     fn name(&self) -> String {
         format!("Z{}", self.a_mask)
     }
 
+    fn qasm(&self) -> Option<String> {
+        Some("z".to_string())
+    }
+
     fn acts_on(&self) -> N {
         self.a_mask
     }
 
+    fn remap(self, f: &dyn Fn(N) -> N) -> AtomicOpDispatch {
+        Self {
+            a_mask: f(self.a_mask),
+        }
+        .this()
+    }
+
     fn this(self) -> dispatch::AtomicOpDispatch {
         dispatch::AtomicOpDispatch::Z(self)
     }
@@ -0,0 +1,228 @@
+#[cfg(feature = "multi-thread")]
+use rayon::prelude::*;
+
+use crate::{
+    math::types::*,
+    operator::{Applicable, MultiOp, SingleOp},
+};
+
+const MAX_PHASE_SPAN: u32 = 12;
+
+fn span(mask: N) -> u32 {
+    match mask {
+        0 => 0,
+        _ => N::BITS - mask.leading_zeros() - mask.trailing_zeros(),
+    }
+}
+
+#[derive(Clone)]
+enum Step {
+    Gate(Box<SingleOp>),
+    Phase { mask: N, shift: u32, phases: Vec<C> },
+}
+
+impl Step {
+    fn phase(&self, idx: N) -> C {
+        match self {
+            Step::Phase {
+                mask,
+                shift,
+                phases,
+            } => phases[(idx & mask) >> shift],
+            Step::Gate(_) => unreachable!(),
+        }
+    }
+
+    fn apply(&self, psi_i: &[C], psi_o: &mut Vec<C>) {
+        match self {
+            Step::Gate(op) => op.apply(psi_i, psi_o),
+            Step::Phase { .. } => psi_o
+                .iter_mut()
+                .enumerate()
+                .for_each(|(idx, psi)| *psi = psi_i[idx] * self.phase(idx)),
+        }
+    }
+
+    #[cfg(feature = "multi-thread")]
+    fn apply_sync(&self, psi_i: &[C], psi_o: &mut Vec<C>) {
+        match self {
+            Step::Gate(op) => op.apply_sync(psi_i, psi_o),
+            Step::Phase { .. } => psi_o
+                .par_iter_mut()
+                .enumerate()
+                .for_each(|(idx, psi)| *psi = psi_i[idx] * self.phase(idx)),
+        }
+    }
+}
+
+/// Quantum operation, prepared for repeated application to register of fixed size.
+///
+/// [`CompiledOp`] is produced by [`MultiOp::compile`].
+/// During compilation identity gates and barriers are dropped
+/// and consecutive diagonal gates (e.g. *Z*, *S*, *T*, *RZ* and their controlled versions)
+/// are merged into a single precomputed phase table,
+/// so register is traversed once for the whole run of them instead of once per gate.
+/// It is applied via [`QReg::apply_compiled`](crate::register::QReg::apply_compiled).
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// let circuit = op::qft(0b1111);
+/// let compiled = circuit.compile(4);
+///
+/// let mut q = QReg::new(4);
+/// for _ in 0..100 {
+///     q.apply_compiled(&compiled);
+/// }
+/// ```
+#[derive(Clone)]
+pub struct CompiledOp {
+    q_num: N,
+    steps: Vec<Step>,
+    source: MultiOp,
+}
+
+impl CompiledOp {
+    pub(crate) fn new(source: &MultiOp, q_num: N) -> Self {
+        let mut steps = vec![];
+        let mut run = vec![];
+        let mut run_mask = 0;
+
+        for op in source.iter().filter(|op| !op.is_identity()) {
+            if op.is_diagonal() {
+                let mask = run_mask | op.act_on();
+                if span(mask) <= MAX_PHASE_SPAN {
+                    run.push(op);
+                    run_mask = mask;
+                    continue;
+                }
+            }
+
+            Self::flush(&mut steps, &mut run, run_mask);
+            run_mask = 0;
+
+            if op.is_diagonal() && span(op.act_on()) <= MAX_PHASE_SPAN {
+                run.push(op);
+                run_mask = op.act_on();
+            } else {
+                steps.push(Step::Gate(Box::new(op.clone())));
+            }
+        }
+        Self::flush(&mut steps, &mut run, run_mask);
+
+        Self {
+            q_num,
+            steps,
+            source: source.clone(),
+        }
+    }
+
+    fn flush(steps: &mut Vec<Step>, run: &mut Vec<&SingleOp>, run_mask: N) {
+        match run.len() {
+            0 => {}
+            1 => steps.push(Step::Gate(Box::new(run[0].clone()))),
+            _ => {
+                let shift = run_mask.trailing_zeros();
+                let phases = (0..1 << span(run_mask))
+                    .map(|idx: N| {
+                        let idx = (idx << shift) & run_mask;
+                        run.iter().map(|op| op.diagonal(idx).unwrap()).product()
+                    })
+                    .collect();
+                steps.push(Step::Phase {
+                    mask: run_mask,
+                    shift,
+                    phases,
+                });
+            }
+        }
+        run.clear();
+    }
+
+    /// Number of qubits, for which operation was compiled.
+    pub fn q_num(&self) -> N {
+        self.q_num
+    }
+
+    /// Return operation, from which this one was compiled.
+    pub fn source(&self) -> &MultiOp {
+        &self.source
+    }
+
+    pub(crate) fn apply(&self, psi: &mut Vec<C>, buffer: &mut Vec<C>) {
+        for step in &self.steps {
+            step.apply(psi, buffer);
+            std::mem::swap(psi, buffer);
+        }
+    }
+
+    #[cfg(feature = "multi-thread")]
+    pub(crate) fn apply_sync(&self, psi: &mut Vec<C>, buffer: &mut Vec<C>) {
+        for step in &self.steps {
+            step.apply_sync(psi, buffer);
+            std::mem::swap(psi, buffer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        math::{consts::TOLERANCE, types::*},
+        prelude::*,
+    };
+
+    fn assert_equivalent(op: &MultiOp, q_num: N, repeat: N) {
+        let compiled = op.compile(q_num);
+
+        let mut q1 = QReg::new(q_num);
+        q1.apply(&op::h((1 << q_num) - 1));
+        let mut q2 = q1.clone();
+
+        for _ in 0..repeat {
+            q1.apply(op);
+            q2.apply_compiled(&compiled);
+        }
+
+        q1.get_polar()
+            .into_iter()
+            .zip(q2.get_polar())
+            .for_each(|((r1, phi1), (r2, phi2))| {
+                let (z1, z2) = (C::from_polar(r1, phi1), C::from_polar(r2, phi2));
+                assert!((z1 - z2).norm() < 10. * TOLERANCE);
+            });
+    }
+
+    #[test]
+    fn merge_diagonal_gates() {
+        use super::Step;
+
+        let compiled = op::qft(0b111).compile(3);
+        let kinds = compiled
+            .steps
+            .iter()
+            .map(|step| matches!(step, Step::Phase { .. }))
+            .collect::<Vec<_>>();
+        assert_eq!(kinds, [false, true, false, false, false]);
+
+        let compiled = (op::id() * op::barrier(0b11) * op::x(0b01)).compile(2);
+        assert_eq!(compiled.steps.len(), 1);
+        assert_eq!(compiled.q_num(), 2);
+    }
+
+    #[test]
+    fn equivalence() {
+        let circuit = op::h(0b11111)
+            * op::z(0b00011)
+            * op::s(0b00100).dgr()
+            * op::t(0b01000).c(0b00001).unwrap()
+            * op::rzz(0.7, 0b10010)
+            * op::rz(1.3, 0b00100).c(0b11000).unwrap()
+            * op::barrier(0b11111)
+            * op::rx(0.4, 0b00010)
+            * op::rz(-0.9, 0b10000);
+
+        assert_equivalent(&circuit, 5, 7);
+        assert_equivalent(&op::qft(0b11111), 5, 3);
+        assert_equivalent(&op::qft((1 << 14) - 1), 14, 1);
+    }
+}
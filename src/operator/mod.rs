@@ -15,6 +15,8 @@
 //! * [`MultiOp`] contains a queue of [`SingleOp`]'s, which are applied sequentially;
 //! * [`SingleOp`] does not have public constructor, so the only usage of it is to be stored in [`MultiOp`].
 //!
+//! Plain ```Vec<SingleOp>``` is also applicable and behaves as [`MultiOp`] with the same gates.
+//!
 //! # [`Applicable`] trait
 //!
 //! Trait represents a generalized quantum operator.
@@ -87,15 +89,20 @@
 //!
 //! # Gate's modifiers - [`.c(...)`](crate::prelude::Applicable::c) and [`.dgr()`](crate::prelude::Applicable::dgr)
 
-pub use self::{applicable::*, multi::MultiOp, single::SingleOp};
+pub(crate) use self::single::{FrameUpdate, PauliFrame};
+pub use self::{
+    applicable::*, compiled::CompiledOp, multi::MultiOp, single::SingleOp, traced::TracedOp,
+};
 use self::{multi::*, single::*};
 use crate::math::{consts::*, types::*};
 
 pub mod applicable;
 
 mod atomic;
+mod compiled;
 mod multi;
 mod single;
+mod traced;
 
 /// [`Identity`](id) gate.
 ///
@@ -114,6 +121,26 @@ pub fn id() -> MultiOp {
     MultiOp::default()
 }
 
+/// [`Barrier`](barrier) marker.
+///
+/// Unlike [`Identity`](id), which acts on nothing, barrier reports the given qubits
+/// in [`act_on()`](crate::prelude::Applicable::act_on),
+/// so layout and scheduling passes could treat it as a boundary between gates.
+///
+/// Barrier has no physical effect: it does not change the state, is skipped when applied to register,
+/// and is not affected by the [`NoiseModel`](crate::prelude::NoiseModel).
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// let barrier = op::barrier(0b101);
+///
+/// assert_eq!(barrier.act_on(), 0b101);
+/// ```
+#[inline(always)]
+pub fn barrier(a_mask: N) -> MultiOp {
+    single::barrier(a_mask).into()
+}
+
 /// Pauli [`X`](x) gate, aka NOT gate.
 ///
 /// Performs negation for given qubit.
@@ -137,6 +164,34 @@ pub fn x(a_mask: N) -> MultiOp {
     pauli::x(a_mask).into()
 }
 
+/// Controlled [`X`](x) gate, aka [`CNOT`](cnot).
+///
+/// Negates *target* qubits, if all *ctrl* qubits are in state |1>.
+/// This is a shorthand for ```op::x(target).c(ctrl).unwrap()```.
+///
+/// # Panics
+///
+/// Panics, if *ctrl* and *target* masks overlap.
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// let cnot = op::cnot(0b01, 0b10);
+///
+/// assert_eq!(cnot, op::x(0b10).c(0b01).unwrap());
+/// ```
+#[inline(always)]
+pub fn cnot(ctrl: N, target: N) -> MultiOp {
+    x(target)
+        .c(ctrl)
+        .expect("Control and target masks should not overlap!")
+}
+
+/// Alias for [`CNOT`](cnot) gate.
+#[inline(always)]
+pub fn cx(ctrl: N, target: N) -> MultiOp {
+    cnot(ctrl, target)
+}
+
 /// *X* rotation gate.
 ///
 /// Performs ```phase``` radians rotation around X axis on a Bloch sphere.
@@ -500,6 +555,288 @@ pub fn u3(the: R, phi: R, lam: R, a_mask: N) -> MultiOp {
     rz(lam, a_mask) * ry(the, a_mask) * rz(phi, a_mask)
 }
 
+/// Controlled [`U3(θ,φ,λ)`](u3) gate.
+///
+/// Applies [`U3(θ,φ,λ)`](u3) to the *target* qubits, if all *ctrl* qubits are in state |1>.
+/// Since [`U3`](u3) is decomposed into [`RZ`](rz) and [`RY`](ry) rotations, every factor is controlled.
+/// Returns [`None`], if *ctrl* and *target* overlap.
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// let cu3 = op::cu3(1.0, 2.0, 3.0, 0b01, 0b10).unwrap();
+/// # assert_eq!(cu3, op::u3(1.0, 2.0, 3.0, 0b10).c(0b01).unwrap());
+/// ```
+#[inline(always)]
+pub fn cu3(the: R, phi: R, lam: R, ctrl: N, target: N) -> Option<MultiOp> {
+    u3(the, phi, lam, target).c(ctrl)
+}
+
+/// Controlled [`RX(φ)`](rx) gate.
+///
+/// Applies [`RX(φ)`](rx) to the *target* qubit, if all *ctrl* qubits are in state |1>.
+/// Returns [`None`], if *ctrl* and *target* overlap.
+#[inline(always)]
+pub fn crx(phase: R, ctrl: N, target: N) -> Option<MultiOp> {
+    rx(phase, target).c(ctrl)
+}
+
+/// Controlled [`RY(φ)`](ry) gate.
+///
+/// Applies [`RY(φ)`](ry) to the *target* qubit, if all *ctrl* qubits are in state |1>.
+/// Returns [`None`], if *ctrl* and *target* overlap.
+#[inline(always)]
+pub fn cry(phase: R, ctrl: N, target: N) -> Option<MultiOp> {
+    ry(phase, target).c(ctrl)
+}
+
+/// Controlled [`RZ(φ)`](rz) gate.
+///
+/// Applies [`RZ(φ)`](rz) to the *target* qubit, if all *ctrl* qubits are in state |1>.
+/// Returns [`None`], if *ctrl* and *target* overlap.
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// let crz = op::crz(1.0, 0b01, 0b10).unwrap();
+/// # assert_eq!(crz, op::rz(1.0, 0b10).c(0b01).unwrap());
+/// ```
+#[inline(always)]
+pub fn crz(phase: R, ctrl: N, target: N) -> Option<MultiOp> {
+    rz(phase, target).c(ctrl)
+}
+
+/// Multi-controlled [`RZ(φ)`](rz) gate.
+///
+/// Applies [`RZ(φ)`](rz) to the single *target* qubit, if all *ctrl* qubits are in state |1>,
+/// which is the building block of phase oracles.
+/// It is the same as [`crz`], but also checks, that *target* is a single qubit.
+/// Returns [`None`], if *target* is not a single qubit or *ctrl* and *target* overlap.
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// let mcrz = op::mcrz(1.0, 0b0111, 0b1000).unwrap();
+/// # assert_eq!(mcrz, op::rz(1.0, 0b1000).c(0b0111).unwrap());
+/// assert!(op::mcrz(1.0, 0b0111, 0b0100).is_none());
+/// assert!(op::mcrz(1.0, 0b0011, 0b1100).is_none());
+/// ```
+#[inline(always)]
+pub fn mcrz(phase: R, ctrl: N, target: N) -> Option<MultiOp> {
+    if target.count_ones() != 1 {
+        return None;
+    }
+    crz(phase, ctrl, target)
+}
+
+/// [`RZZ(φ)`](rzz) gate, decomposed into [`CNOT`](cnot) and [`RZ(φ)`](rz) gates.
+///
+/// ```RZZ(φ) = CNOT · RZ(φ) · CNOT```, where the lower qubit of *ab_mask* is control of both [`CNOT`](cnot)s,
+/// and [`RZ(φ)`](rz) rotates the upper one.
+/// This is useful for targeting hardware, where Ising coupling gates are not native.
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// let rzz = op::rzz_decomposed(1.0, 0b11);
+/// # assert_eq!(rzz, op::cnot(0b01, 0b10) * op::rz(1.0, 0b10) * op::cnot(0b01, 0b10));
+/// assert!(rzz.is_equivalent(&op::rzz(1.0, 0b11), 2, 1e-9));
+/// ```
+///
+/// # Panics
+///
+/// Panics if *ab_mask* does not contain 2 bits.
+pub fn rzz_decomposed(phase: R, ab_mask: N) -> MultiOp {
+    assert_eq!(ab_mask.count_ones(), 2, "Mask should contain 2 bit!");
+    let a_mask = ab_mask & ab_mask.wrapping_neg();
+    let b_mask = ab_mask ^ a_mask;
+    cnot(a_mask, b_mask) * rz(phase, b_mask) * cnot(a_mask, b_mask)
+}
+
+/// [`RXX(φ)`](rxx) gate, decomposed into [`CNOT`](cnot) and single qubit gates.
+///
+/// It is [`RZZ(φ)`](rzz_decomposed) in the basis, changed by [`H`](h) gates on both qubits.
+///
+/// # Panics
+///
+/// Panics if *ab_mask* does not contain 2 bits.
+pub fn rxx_decomposed(phase: R, ab_mask: N) -> MultiOp {
+    h(ab_mask) * rzz_decomposed(phase, ab_mask) * h(ab_mask)
+}
+
+/// [`RYY(φ)`](ryy) gate, decomposed into [`CNOT`](cnot) and single qubit gates.
+///
+/// It is [`RXX(φ)`](rxx_decomposed) in the basis, changed by [`S`](s) gates on both qubits.
+///
+/// # Panics
+///
+/// Panics if *ab_mask* does not contain 2 bits.
+pub fn ryy_decomposed(phase: R, ab_mask: N) -> MultiOp {
+    s(ab_mask).dgr() * rxx_decomposed(phase, ab_mask) * s(ab_mask)
+}
+
+/// Controlled version of the whole *block* of gates.
+///
+/// Applies unitary of *block* if all *ctrl* qubits are in state |1>, and does nothing otherwise:
+///
+/// ```C(U) = |0><0| ⊗ I + |1><1| ⊗ U```
+///
+/// Since gates of *block* are applied sequentially and none of them touches *ctrl* qubits,
+/// ```C(U1·U2) = C(U1)·C(U2)```, so controlling the whole block is the same as controlling every gate.
+/// Returns [`None`], if *block* acts on any of *ctrl* qubits.
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// let bell = op::h(0b001) * op::cnot(0b001, 0b010);
+/// let c_bell = op::control_block(bell, 0b100).unwrap();
+///
+/// let mut q = QReg::with_state(3, 0b100);
+/// q.apply(&c_bell);
+/// # assert!((q.get_probabilities()[0b100] - 0.5).abs() < 1e-9);
+/// # assert!((q.get_probabilities()[0b111] - 0.5).abs() < 1e-9);
+/// ```
+#[inline(always)]
+pub fn control_block(block: MultiOp, ctrl: N) -> Option<MultiOp> {
+    block.c(ctrl)
+}
+
+/// Single qubit gate with a custom *matrix*.
+///
+/// Matrix is given in row-major order and should be unitary up to a few ULPs, otherwise [`None`] is returned.
+/// For matrices with larger numerical errors use [`unitary_with_tol`].
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// const O: C = C { re: 0.0, im: 0.0 };
+/// const I: C = C { re: 1.0, im: 0.0 };
+///
+/// let x = op::unitary([O, I, I, O], 0b1).unwrap();
+/// # assert!(x.is_equivalent(&op::x(0b1), 1, 1e-9));
+/// # assert!(op::unitary([I, I, I, O], 0b1).is_none());
+/// ```
+#[inline(always)]
+pub fn unitary(matrix: M1, a_mask: N) -> Option<MultiOp> {
+    pauli::u1(a_mask, matrix).map(MultiOp::from)
+}
+
+/// Single qubit gate with a custom *matrix*, which is unitary up to absolute tolerance *tol*.
+///
+/// Same as [`unitary`], but accepts matrices, assembled from lower-precision sources.
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// let h = C::new(std::f64::consts::FRAC_1_SQRT_2 as R + 1e-5, 0.0);
+///
+/// assert!(op::unitary([h, h, h, -h], 0b1).is_none());
+/// assert!(op::unitary_with_tol([h, h, h, -h], 0b1, 1e-4).is_some());
+/// ```
+#[inline(always)]
+pub fn unitary_with_tol(matrix: M1, a_mask: N, tol: R) -> Option<MultiOp> {
+    pauli::u1_eps(a_mask, matrix, tol).map(MultiOp::from)
+}
+
+/// Decompose single qubit gate with a custom *matrix* into [`RZ`](rz) and [`RY`](ry) rotations.
+///
+/// Any unitary matrix is equal to ```exp(iα)·RZ(φ)·RY(θ)·RZ(λ)``` for some Euler angles,
+/// so result is [`U3(θ,φ,λ)`](u3) on the *qubit*, i.e. the sequence of ```RZ(λ)```, ```RY(θ)``` and ```RZ(φ)```,
+/// which is equal to *matrix* up to the global phase ```exp(iα)```.
+/// Matrix is given in row-major order and should be unitary up to absolute tolerance ```1e-10```
+/// (```1e-5``` in single precision mode).
+/// Returns [`None`], if matrix is not unitary or *qubit* is not a single qubit.
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// let h = C::new(std::f64::consts::FRAC_1_SQRT_2 as R, 0.0);
+/// let zyz = op::zyz_decompose([h, h, h, -h], 0b1).unwrap();
+///
+/// assert_eq!(zyz.len(), 3);
+/// assert!(zyz.is_equivalent(&op::h(0b1), 1, 1e-5));
+/// ```
+pub fn zyz_decompose(matrix: M1, qubit: N) -> Option<MultiOp> {
+    const EPS: R = TOLERANCE;
+
+    if qubit.count_ones() != 1 || !crate::math::matrix::is_unitary_m1_eps(&matrix, EPS) {
+        return None;
+    }
+
+    let [u00, u01, u10, u11] = matrix;
+    let theta = 2.0 * u10.norm().atan2(u00.norm());
+    let det = (u00 * u11 - u01 * u10).arg();
+
+    // Up to the global phase exp(i·det/2): arg(u11) = (φ+λ)/2, arg(u10) = (φ-λ)/2.
+    // If one of them vanishes, only the other combination matters, so λ is set to 0.
+    let (half_sum, half_diff) = match (u00.norm() > EPS, u10.norm() > EPS) {
+        (true, true) => (u11.arg(), u10.arg()),
+        (true, false) => (u11.arg(), u11.arg()),
+        _ => (u10.arg(), u10.arg()),
+    };
+
+    Some(u3(
+        theta,
+        half_sum + half_diff - det,
+        half_sum - half_diff,
+        qubit,
+    ))
+}
+
+/// Two qubit gate with a custom *matrix*.
+///
+/// Matrix is given in row-major order and should be unitary up to a few ULPs, otherwise [`None`] is returned.
+/// Qubit *a_mask* corresponds to the lower bit of row and column indices, and *b_mask* - to the upper one.
+/// For matrices with larger numerical errors use [`unitary2_with_tol`].
+#[inline(always)]
+pub fn unitary2(matrix: M2, a_mask: N, b_mask: N) -> Option<MultiOp> {
+    pauli::u2(a_mask, b_mask, matrix).map(MultiOp::from)
+}
+
+/// Two qubit gate with a custom *matrix*, which is unitary up to absolute tolerance *tol*.
+///
+/// Same as [`unitary2`], but accepts matrices, assembled from lower-precision sources.
+#[inline(always)]
+pub fn unitary2_with_tol(matrix: M2, a_mask: N, b_mask: N, tol: R) -> Option<MultiOp> {
+    pauli::u2_eps(a_mask, b_mask, matrix, tol).map(MultiOp::from)
+}
+
+/// [`Multiplexed`](multiplex) single qubit gate.
+///
+/// Applies *u0* to the *target* qubit, if *ctrl* qubit is in state |0>,
+/// and *u1*, if *ctrl* qubit is in state |1>.
+/// Both *ctrl* and *target* should contain exactly one bit and should not overlap.
+/// Matrices are given in row-major order and should be unitary, otherwise [`None`] is returned.
+///
+/// This is the building block for uniformly controlled rotations, e.g. in state preparation.
+///
+/// Matrix form for [`multiplex`] gate:
+///
+/// <table cellpadding="10pt">
+///     <tr><th>&nbsp;&nbsp;U0&nbsp;&nbsp;</th><th>&nbsp;&nbsp;0&nbsp;&nbsp;</th></tr>
+///     <tr><th>&nbsp;&nbsp;0&nbsp;&nbsp;</th><th>&nbsp;&nbsp;U1&nbsp;&nbsp;</th></tr>
+/// </table>
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// const O: C = C { re: 0.0, im: 0.0 };
+/// const I: C = C { re: 1.0, im: 0.0 };
+///
+/// const ID: [C; 4] = [I, O, O, I];
+/// const X: [C; 4] = [O, I, I, O];
+///
+/// // Controlled NOT gate
+/// let cnot = op::multiplex(0b01, 0b10, ID, X).unwrap();
+/// # assert!(cnot.is_equivalent(&op::x(0b10).c(0b01).unwrap(), 2, 1e-9));
+/// ```
+#[inline(always)]
+pub fn multiplex(ctrl: N, target: N, u0: M1, u1: M1) -> Option<MultiOp> {
+    if ctrl & target != 0 {
+        return None;
+    }
+
+    #[rustfmt::skip]
+    let matrix = [
+        u0[0b00], u0[0b01], C_ZERO, C_ZERO,
+        u0[0b10], u0[0b11], C_ZERO, C_ZERO,
+        C_ZERO, C_ZERO, u1[0b00], u1[0b01],
+        C_ZERO, C_ZERO, u1[0b10], u1[0b11],
+    ];
+    pauli::u2(target, ctrl, matrix).map(MultiOp::from)
+}
+
 /// Discrete Fourier transform ([`QFT`](qft())) for the quantum state's amplitudes.
 ///
 /// Fourier transform with factor 1/&radic;N.
@@ -512,6 +849,39 @@ pub fn qft(a_mask: N) -> MultiOp {
     qft::qft(a_mask)
 }
 
+/// Controlled [`QFT`](qft()).
+///
+/// Applies [`QFT`](qft()) to the *target* qubits, if all *ctrl* qubits are in state |1>.
+/// Every gate of transform, including controlled phases, gets *ctrl* as additional control qubits.
+/// Returns [`None`], if *ctrl* and *target* overlap.
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// let cqft = op::controlled_qft(0b1000, 0b0111).unwrap();
+/// # assert_eq!(cqft, op::qft(0b0111).c(0b1000).unwrap());
+/// ```
+#[inline(always)]
+pub fn controlled_qft(ctrl: N, target: N) -> Option<MultiOp> {
+    qft(target).c(ctrl)
+}
+
+/// Approximate [`QFT`](qft()).
+///
+/// Controlled rotations between qubits, which are more than *cutoff* positions apart, are omitted.
+/// Since their angles decrease exponentially with the distance, they contribute negligibly,
+/// while approximate transform takes only O(n&middot;cutoff) gates.
+/// For *cutoff* &ge; n - 1 it is equal to [`QFT`](qft()).
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// assert_eq!(op::qft_approx(0b1111, 3), op::qft(0b1111));
+/// assert!(op::qft_approx(0b1111, 1).len() < op::qft(0b1111).len());
+/// ```
+#[inline(always)]
+pub fn qft_approx(a_mask: N, cutoff: N) -> MultiOp {
+    qft::qft_approx(a_mask, cutoff)
+}
+
 /// Discrete Fourier transform with qubits' swap
 ///
 /// [`QFT`](qft()) is differ from real DFT by a bit order of amplitudes indices.
@@ -521,6 +891,195 @@ pub fn qft_swapped(a_mask: N) -> MultiOp {
     qft::qft_swapped(a_mask)
 }
 
+/// Diffusion operator for Grover's search.
+///
+/// Performs inversion about the mean for amplitudes of masked qubits,
+/// i.e. reflection about the uniform superposition |s>:
+///
+/// ```D = H X (C..CZ) X H = I - 2 |s><s|```
+///
+/// Combined with oracle, which flips the phase of marked states, it forms one Grover's iteration:
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// let oracle = op::z(0b10).c(0b01).unwrap();
+/// let iteration = oracle * op::grover_diffusion(0b11);
+///
+/// let mut q = QReg::new(2);
+/// q.apply(&(op::h(0b11) * iteration));
+/// # assert!((q.get_probabilities()[0b11] - 1.0).abs() < 1e-9);
+/// ```
+#[inline(always)]
+pub fn grover_diffusion(mask: N) -> MultiOp {
+    if mask == 0 {
+        return id();
+    }
+
+    let target = 1 << (N::BITS - 1 - mask.leading_zeros());
+    h(mask) * x(mask) * z(target).c(mask & !target).unwrap() * x(mask) * h(mask)
+}
+
+/// Quantum phase estimation of *unitary*'s eigenvalue.
+///
+/// Hadamard gates are applied to *counting* qubits, then the k-th of them (from the lowest one)
+/// controls 2<sup>k</sup> applications of *unitary*, and finally inverse discrete Fourier transform is applied to *counting* qubits.
+/// If *target* qubits hold an eigenstate of *unitary* with eigenvalue e<sup>2&pi;i&phi;</sup>,
+/// measurement of *counting* qubits gives the closest approximation of &phi;&middot;2<sup>n</sup>,
+/// where *n* is the number of *counting* qubits.
+/// Returns [`None`], if *unitary* acts on qubits outside *target* or *counting* overlaps *target*.
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// // Eigenvalue of |1> is exp(2πi * 3/8)
+/// let unitary = op::rz(1.5 * std::f64::consts::PI as R, 0b0001);
+/// let qpe = op::phase_estimation(0b1110, 0b0001, &unitary).unwrap();
+///
+/// let mut q = QReg::with_state(4, 0b0001);
+/// q.apply(&qpe);
+/// assert_eq!(q.measure_mask(0b1110).get() >> 1, 3);
+/// ```
+pub fn phase_estimation(counting: N, target: N, unitary: &MultiOp) -> Option<MultiOp> {
+    if counting & target != 0 || unitary.act_on() & !target != 0 {
+        return None;
+    }
+
+    let mut res = h(counting);
+    let mut power = unitary.clone();
+    for ctrl in crate::math::bits_iter::BitsIter::from(counting) {
+        res *= power.clone().c(ctrl)?;
+        power = power.clone() * power;
+    }
+
+    // Inverse of swapped DFT, built of exact controlled phase gates
+    let qubits = crate::math::bits_iter::BitsIter::from(counting).collect::<Vec<_>>();
+    let len = qubits.len();
+    for i in 0..len / 2 {
+        res *= swap(qubits[i] | qubits[len - 1 - i]);
+    }
+    for i in 0..len {
+        for j in 0..i {
+            let phase = C::from_polar(1.0, -PI / (1 << (i - j)) as R);
+            res *= self::unitary([C_ONE, C_ZERO, C_ZERO, phase], qubits[i])?.c(qubits[j])?;
+        }
+        res *= h(qubits[i]);
+    }
+
+    Some(res)
+}
+
+/// Apply gate, produced by *gate_fn*, to each qubit of virtual register *vreg* separately.
+///
+/// *gate_fn* receives a single-bit mask of every qubit of *vreg* (from the lowest one),
+/// so any single qubit gate constructor could be passed directly.
+/// Unlike the gate on the whole mask, result consists of separate gates, one per qubit,
+/// which is useful for scheduling analysis.
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// let v = VReg::new(6);
+/// let even = VReg::from(v[|i| i % 2 == 0]);
+///
+/// let gates = op::apply_each(op::h, &even);
+/// assert_eq!(gates.len(), 3);
+/// # assert!(gates.is_equivalent(&op::h(0b010101), 6, 1e-9));
+/// ```
+pub fn apply_each<F>(gate_fn: F, vreg: &crate::register::VReg) -> MultiOp
+where
+    F: Fn(N) -> MultiOp,
+{
+    vreg.1.iter().fold(id(), |op, &q| op * gate_fn(q))
+}
+
+/// Wrap operation *inner* to report every applied gate with its elapsed time.
+///
+/// Result of application is the same as for *inner*, see [`TracedOp`] for details.
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// let qft = op::traced(op::qft(0b111), "qft")
+///     .with_logger(|label, name, elapsed| println!("[{label}] {name}: {elapsed:?}"));
+///
+/// let mut q = QReg::new(3);
+/// // Prints names of QFT's gates labeled with "qft"
+/// q.apply(&qft);
+/// ```
+pub fn traced(inner: MultiOp, label: &str) -> TracedOp {
+    TracedOp::new(inner, label)
+}
+
+/// Encoder of 3-qubit bit-flip repetition code.
+///
+/// Copies basis state of *data* qubit into both *ancilla* qubits, i.e.
+/// ```a|0> + b|1>``` becomes ```a|000> + b|111>```.
+/// Any single [`X`](x) error on the code qubits could be found by [`bit_flip_syndrome`].
+///
+/// # Panics
+///
+/// Panics if *data* is not a single qubit, *ancilla* is not a pair of qubits or they overlap.
+pub fn bit_flip_encode(data: N, ancilla: N) -> MultiOp {
+    assert!(
+        data.count_ones() == 1 && ancilla.count_ones() == 2 && data & ancilla == 0,
+        "Code requires one data qubit and two distinct ancilla qubits!"
+    );
+    cnot(data, ancilla)
+}
+
+/// Syndrome extraction for 3-qubit bit-flip repetition code.
+///
+/// For *code* qubits q0, q1, q2 (from the lowest bit) parities ```q0⊕q1``` and ```q1⊕q2```
+/// are written into the lower and the upper qubit of *syndrome* respectively.
+/// Thus, measured syndrome 01, 11 or 10 points at flipped q0, q1 or q2, and 00 means no error.
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// let mut q = QReg::new(5);
+/// q.apply(&(op::bit_flip_encode(0b001, 0b110) * op::x(0b010)));
+/// q.apply(&op::bit_flip_syndrome(0b00111, 0b11000));
+///
+/// assert_eq!(q.measure_mask(0b11000).get(), 0b11000);
+/// ```
+///
+/// # Panics
+///
+/// Panics if *code* is not 3 qubits, *syndrome* is not 2 qubits or they overlap.
+pub fn bit_flip_syndrome(code: N, syndrome: N) -> MultiOp {
+    assert!(
+        code.count_ones() == 3 && syndrome.count_ones() == 2 && code & syndrome == 0,
+        "Syndrome requires three code qubits and two distinct syndrome qubits!"
+    );
+
+    let mut q = crate::math::bits_iter::BitsIter::from(code);
+    let (q0, q1, q2) = (q.next().unwrap(), q.next().unwrap(), q.next().unwrap());
+    let s0 = syndrome & syndrome.wrapping_neg();
+    let s1 = syndrome & !s0;
+
+    cnot(q0, s0) * cnot(q1, s0) * cnot(q1, s1) * cnot(q2, s1)
+}
+
+/// Encoder of 3-qubit phase-flip repetition code.
+///
+/// Same as [`bit_flip_encode`], but in Hadamard basis:
+/// ```a|0> + b|1>``` becomes ```a|+++> + b|--->```, so single [`Z`](z) error is found by [`phase_flip_syndrome`].
+///
+/// # Panics
+///
+/// Panics if *data* is not a single qubit, *ancilla* is not a pair of qubits or they overlap.
+pub fn phase_flip_encode(data: N, ancilla: N) -> MultiOp {
+    bit_flip_encode(data, ancilla) * h(data | ancilla)
+}
+
+/// Syndrome extraction for 3-qubit phase-flip repetition code.
+///
+/// Code qubits are rotated back to computational basis, and the syndrome is the same as for [`bit_flip_syndrome`].
+///
+/// # Panics
+///
+/// Panics if *code* is not 3 qubits, *syndrome* is not 2 qubits or they overlap.
+pub fn phase_flip_syndrome(code: N, syndrome: N) -> MultiOp {
+    let extract = bit_flip_syndrome(code, syndrome);
+    h(code) * extract * h(code)
+}
+
 #[cfg(test)]
 pub fn bench_circuit() -> MultiOp {
     MultiOp::default()
@@ -4,7 +4,13 @@ use std::{
 };
 
 pub use super::Applicable;
-use crate::{math::types::*, operator::single::*};
+use crate::{
+    math::{
+        consts::{C_ONE, C_ZERO},
+        types::*,
+    },
+    operator::{single::*, Noise},
+};
 
 /// Quantum operation's queue.
 ///
@@ -69,6 +75,200 @@ impl MultiOp {
             .zip(suffix.iter().rev())
             .all(|(a, b)| a == b)
     }
+
+    /// Fuse consecutive single qubit gates, which act on the same qubit, into one gate.
+    ///
+    /// Gates are fused only if no other gate touches their qubit in between,
+    /// so the resulting operation is equivalent to the original one,
+    /// but takes less passes over the register, e.g. for [`U3`](crate::operator::u3())-decomposed circuits.
+    /// Fused gate is a custom unitary gate with product of gates' matrices.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let op = op::u3(1.0, 2.0, 3.0, 0b01) * op::cnot(0b01, 0b10) * op::h(0b01) * op::s(0b01);
+    /// let fused = op.clone().fuse_single_qubit();
+    ///
+    /// assert_eq!(fused.len(), 3);
+    /// assert!(fused.is_equivalent(&op, 2, 1e-9));
+    /// ```
+    pub fn fuse_single_qubit(self) -> Self {
+        fn mul(a: &M1, b: &M1) -> M1 {
+            [
+                a[0b00] * b[0b00] + a[0b01] * b[0b10],
+                a[0b00] * b[0b01] + a[0b01] * b[0b11],
+                a[0b10] * b[0b00] + a[0b11] * b[0b10],
+                a[0b10] * b[0b01] + a[0b11] * b[0b11],
+            ]
+        }
+
+        fn flush(fused: &mut VecDeque<SingleOp>, pending: &mut Vec<Vec<SingleOp>>, mask: N) {
+            pending.retain_mut(|ops| {
+                if ops[0].act_on() & mask == 0 {
+                    return true;
+                }
+                if ops.len() == 1 {
+                    fused.push_back(ops.pop().unwrap());
+                } else {
+                    let matrix = ops.iter().fold([C_ONE, C_ZERO, C_ZERO, C_ONE], |acc, op| {
+                        mul(&op.matrix_m1().unwrap(), &acc)
+                    });
+                    fused.push_back(pauli::u1(ops[0].act_on(), matrix).unwrap());
+                }
+                false
+            });
+        }
+
+        let mut fused = VecDeque::with_capacity(self.len());
+        let mut pending: Vec<Vec<SingleOp>> = vec![];
+
+        for op in self.0 {
+            if op.matrix_m1().is_some() {
+                if let Some(ops) = pending
+                    .iter_mut()
+                    .find(|ops| ops[0].act_on() == op.act_on())
+                {
+                    ops.push(op);
+                } else {
+                    pending.push(vec![op]);
+                }
+            } else {
+                flush(&mut fused, &mut pending, op.act_on());
+                fused.push_back(op);
+            }
+        }
+        flush(&mut fused, &mut pending, !0);
+
+        Self(fused)
+    }
+
+    /// Reorder gates, so that gates, which touch the same qubits, are adjacent.
+    ///
+    /// Every gate is moved towards the beginning of operation, past all preceding gates on disjoint qubits,
+    /// until it meets a gate, which shares qubits with it.
+    /// Only gates with disjoint [`act_on`](crate::operator::Applicable::act_on) masks are swapped,
+    /// so the resulting operation is equivalent to the original one.
+    /// Grouped gates are also better candidates for [`fuse_single_qubit`](MultiOp::fuse_single_qubit).
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let op = op::h(0b01) * op::x(0b10) * op::z(0b01) * op::y(0b10);
+    /// let scheduled = op.clone().optimize_schedule();
+    ///
+    /// assert_eq!(scheduled, op::x(0b10) * op::y(0b10) * op::h(0b01) * op::z(0b01));
+    /// assert!(scheduled.is_equivalent(&op, 2, 1e-9));
+    /// ```
+    pub fn optimize_schedule(self) -> Self {
+        let mut scheduled: VecDeque<SingleOp> = VecDeque::with_capacity(self.len());
+
+        for op in self.0 {
+            let mask = op.act_on();
+            let idx = scheduled
+                .iter()
+                .rposition(|prev| prev.act_on() & mask != 0)
+                .map_or(0, |idx| idx + 1);
+            scheduled.insert(idx, op);
+        }
+
+        Self(scheduled)
+    }
+
+    /// Return mask of qubits, which could influence the state of *output* qubits after this operation.
+    ///
+    /// Gates are walked from the last one to the first one, and qubits of every gate,
+    /// which touches already collected qubits, are added to the light cone.
+    /// Identity gates and barriers are skipped.
+    /// Qubits outside the light cone are irrelevant for the marginal distribution of *output* qubits.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let op = op::h(0b0001) * op::cnot(0b0001, 0b0010) * op::x(0b0100) * op::cnot(0b0010, 0b1000);
+    ///
+    /// assert_eq!(op.light_cone(0b1000), 0b1011);
+    /// assert_eq!(op.light_cone(0b0100), 0b0100);
+    /// ```
+    pub fn light_cone(&self, output: N) -> N {
+        self.iter()
+            .rev()
+            .filter(|op| !op.is_identity())
+            .fold(output, |cone, op| {
+                if op.act_on() & cone != 0 {
+                    cone | op.act_on()
+                } else {
+                    cone
+                }
+            })
+    }
+
+    /// Prepare operation for repeated application to register of *q_num* qubits.
+    ///
+    /// See [`CompiledOp`](crate::operator::CompiledOp) for details.
+    pub fn compile(&self, q_num: N) -> crate::operator::CompiledOp {
+        crate::operator::CompiledOp::new(self, q_num)
+    }
+
+    /// Export operation as OpenQASM 2.0 source for register of *q_num* qubits.
+    ///
+    /// Only gates of standard ```qelib1.inc``` are emitted, one statement per target qubit,
+    /// so the source could be read by other tools, as well as by [`Ast`](crate::qasm::Ast) with *interpreter* feature.
+    /// Returns [`None`], if operation acts on qubits beyond *q_num*
+    /// or contains a gate without ```qelib1.inc``` counterpart,
+    /// e.g. [`RYY`](crate::operator::ryy), [`iSWAP`](crate::operator::i_swap) or [`X`](crate::operator::x) with more than 2 control qubits.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let op = op::h(0b011) * op::cnot(0b001, 0b100);
+    ///
+    /// assert_eq!(
+    ///     op.to_qasm(3).unwrap(),
+    ///     "OPENQASM 2.0;\ninclude \"qelib1.inc\";\nqreg q[3];\nh q[0];\nh q[1];\ncx q[0], q[2];\n"
+    /// );
+    /// # assert_eq!(op::x(0b1000).c(0b0111).unwrap().to_qasm(4), None);
+    /// ```
+    pub fn to_qasm(&self, q_num: N) -> Option<String> {
+        if self.act_on().checked_shr(q_num as u32).unwrap_or(0) != 0 {
+            return None;
+        }
+
+        let mut source = format!(
+            "OPENQASM 2.0;\ninclude \"qelib1.inc\";\nqreg q[{}];\n",
+            q_num
+        );
+        for op in self.iter().filter(|op| op.act_on() != 0) {
+            source.push_str(&op.to_qasm("q")?);
+            source.push('\n');
+        }
+        Some(source)
+    }
+
+    /// Check whether two operations implement the same unitary on *q_num* qubits,
+    /// up to a global phase.
+    /// Both operations should act only on the first *q_num* qubits.
+    ///
+    /// Operations are compared column by column, with every amplitude differing by no more than *tol*.
+    /// Since it takes O(4<sup>n</sup>) memory, this is only feasible for small number of qubits.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let hzh = op::h(0b1) * op::z(0b1) * op::h(0b1);
+    ///
+    /// assert!(hzh.is_equivalent(&op::x(0b1), 1, 1e-9));
+    /// ```
+    pub fn is_equivalent(&self, other: &Self, q_num: N, tol: R) -> bool {
+        let (lhs, rhs) = (self.matrix(q_num), other.matrix(q_num));
+        let (lhs, rhs) = (lhs.iter().flatten(), rhs.iter().flatten());
+
+        let (a, b) = match lhs
+            .clone()
+            .zip(rhs.clone())
+            .max_by(|(a, _), (b, _)| a.norm_sqr().total_cmp(&b.norm_sqr()))
+        {
+            Some(max) => max,
+            None => return true,
+        };
+        let phase = b * a.conj() / a.norm_sqr();
+
+        lhs.zip(rhs).all(|(a, b)| (a * phase - b).norm() <= tol)
+    }
 }
 
 #[doc(hidden)]
@@ -93,35 +293,58 @@ impl std::fmt::Debug for MultiOp {
     }
 }
 
-impl Applicable for MultiOp {
-    fn apply(&self, psi_i: &[C], psi_o: &mut Vec<C>) {
-        let mut psi_i = psi_i.to_vec();
-        self.0.iter().for_each(|op| {
-            op.apply(&psi_i, psi_o);
+// Apply *ops* one after another, using *psi_o* and temporary vector as ping-pong buffers.
+pub(crate) fn apply_sequence<'a, I, F>(ops: I, psi_i: &[C], psi_o: &mut Vec<C>, mut apply: F)
+where
+    I: IntoIterator<Item = &'a SingleOp>,
+    F: FnMut(&SingleOp, &[C], &mut Vec<C>),
+{
+    let mut psi_i = psi_i.to_vec();
+    ops.into_iter()
+        .filter(|op| !op.is_identity())
+        .for_each(|op| {
+            apply(op, &psi_i, psi_o);
             std::mem::swap(&mut psi_i, psi_o);
         });
-        std::mem::swap(&mut psi_i, psi_o);
+    std::mem::swap(&mut psi_i, psi_o);
+}
+
+impl Applicable for MultiOp {
+    fn apply(&self, psi_i: &[C], psi_o: &mut Vec<C>) {
+        apply_sequence(&self.0, psi_i, psi_o, SingleOp::apply);
     }
 
     #[cfg(feature = "multi-thread")]
     fn apply_sync(&self, psi_i: &[C], psi_o: &mut Vec<C>) {
-        let mut psi_i = psi_i.to_vec();
-        self.0.iter().for_each(|op| {
-            op.apply_sync(&psi_i, psi_o);
-            std::mem::swap(&mut psi_i, psi_o);
-        });
-        std::mem::swap(&mut psi_i, psi_o);
+        apply_sequence(&self.0, psi_i, psi_o, SingleOp::apply_sync);
     }
 
     fn act_on(&self) -> N {
         self.0.iter().fold(0, |act, op| act | op.act_on())
     }
 
+    fn apply_noisy(&self, psi_i: &[C], psi_o: &mut Vec<C>, noise: &dyn Noise) {
+        apply_sequence(&self.0, psi_i, psi_o, |op, psi_i, psi_o| {
+            op.apply_noisy(psi_i, psi_o, noise)
+        });
+    }
+
+    #[cfg(feature = "multi-thread")]
+    fn apply_sync_noisy(&self, psi_i: &[C], psi_o: &mut Vec<C>, noise: &dyn Noise) {
+        apply_sequence(&self.0, psi_i, psi_o, |op, psi_i, psi_o| {
+            op.apply_sync_noisy(psi_i, psi_o, noise)
+        });
+    }
+
     fn dgr(self) -> Self {
         let new = self.0.into_iter().map(|op| op.dgr()).rev().collect();
         Self(new)
     }
 
+    fn gates(&self) -> Option<Box<dyn Iterator<Item = &SingleOp> + '_>> {
+        Some(Box::new(self.0.iter()))
+    }
+
     fn c(self, c_mask: N) -> Option<Self> {
         if self.act_on() & c_mask != 0 {
             None
@@ -132,6 +355,60 @@ impl Applicable for MultiOp {
     }
 }
 
+/// Plain sequence of gates, which are applied in order, starting from the first one.
+///
+/// It behaves exactly as [`MultiOp`] with the same gates:
+/// [`dgr`](Applicable::dgr) reverses the sequence and conjugates every gate,
+/// and [`c`](Applicable::c) adds control qubits to every gate.
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// let gates: Vec<SingleOp> = (op::h(0b01) * op::cnot(0b01, 0b10)).iter().cloned().collect();
+///
+/// let mut q = QReg::new(2);
+/// q.apply(&gates);
+/// # assert!((q.get_probabilities()[0b11] - 0.5).abs() < 1e-9);
+/// ```
+impl Applicable for Vec<SingleOp> {
+    fn apply(&self, psi_i: &[C], psi_o: &mut Vec<C>) {
+        apply_sequence(self, psi_i, psi_o, SingleOp::apply);
+    }
+
+    #[cfg(feature = "multi-thread")]
+    fn apply_sync(&self, psi_i: &[C], psi_o: &mut Vec<C>) {
+        apply_sequence(self, psi_i, psi_o, SingleOp::apply_sync);
+    }
+
+    fn act_on(&self) -> N {
+        self.iter().fold(0, |act, op| act | op.act_on())
+    }
+
+    fn apply_noisy(&self, psi_i: &[C], psi_o: &mut Vec<C>, noise: &dyn Noise) {
+        apply_sequence(self, psi_i, psi_o, |op, psi_i, psi_o| {
+            op.apply_noisy(psi_i, psi_o, noise)
+        });
+    }
+
+    #[cfg(feature = "multi-thread")]
+    fn apply_sync_noisy(&self, psi_i: &[C], psi_o: &mut Vec<C>, noise: &dyn Noise) {
+        apply_sequence(self, psi_i, psi_o, |op, psi_i, psi_o| {
+            op.apply_sync_noisy(psi_i, psi_o, noise)
+        });
+    }
+
+    fn dgr(self) -> Self {
+        self.into_iter().map(|op| op.dgr()).rev().collect()
+    }
+
+    fn gates(&self) -> Option<Box<dyn Iterator<Item = &SingleOp> + '_>> {
+        Some(Box::new(self.iter()))
+    }
+
+    fn c(self, c_mask: N) -> Option<Self> {
+        self.into_iter().map(|op| op.c(c_mask)).collect()
+    }
+}
+
 impl From<SingleOp> for MultiOp {
     fn from(single: SingleOp) -> Self {
         Self(
@@ -195,7 +472,10 @@ pub mod qft;
 
 #[cfg(test)]
 mod tests {
-    use crate::prelude::*;
+    use crate::{
+        math::{consts::*, types::*},
+        prelude::*,
+    };
 
     #[test]
     fn ops() {
@@ -207,6 +487,489 @@ mod tests {
         assert_eq!(pend_ops.len(), 3);
     }
 
+    #[test]
+    fn is_equivalent() {
+        const TOL: R = 10. * TOLERANCE;
+
+        assert!((op::h(0b1) * op::h(0b1)).is_equivalent(&op::id(), 1, TOL));
+        assert!(op::u2(0.3, 1.2, 0b1).is_equivalent(&op::u3(FRAC_PI_2, 0.3, 1.2, 0b1), 1, TOL));
+        assert!(op::u3(PI, PI, 0.0, 0b1).is_equivalent(&op::x(0b1), 1, TOL));
+        assert!(op::u3(PI, 0.0, 0.0, 0b1).is_equivalent(&op::y(0b1), 1, TOL));
+        assert!(op::u2(0.0, PI, 0b1).is_equivalent(&op::h(0b1), 1, TOL));
+        assert!(op::u1(FRAC_PI_2, 0b1).is_equivalent(&op::s(0b1), 1, TOL));
+
+        assert!(!op::x(0b1).is_equivalent(&op::y(0b1), 1, TOL));
+        assert!(!op::h(0b1).is_equivalent(&op::id(), 1, TOL));
+        assert!(!op::x(0b01).is_equivalent(&op::x(0b10), 2, TOL));
+        assert!(op::swap(0b11).is_equivalent(
+            &(op::x(0b10).c(0b01).unwrap()
+                * op::x(0b01).c(0b10).unwrap()
+                * op::x(0b10).c(0b01).unwrap()),
+            2,
+            TOL
+        ));
+    }
+
+    #[test]
+    fn cnot() {
+        assert_eq!(op::cnot(0b001, 0b100), op::x(0b100).c(0b001).unwrap());
+        assert_eq!(op::cx(0b011, 0b100), op::x(0b100).c(0b011).unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "Control and target masks should not overlap!")]
+    fn cnot_overlap() {
+        op::cnot(0b011, 0b010);
+    }
+
+    #[test]
+    fn cu3() {
+        const TOL: R = 10. * TOLERANCE;
+
+        let cu3 = op::cu3(1.0, 2.0, 3.0, 0b001, 0b100).unwrap();
+        assert_eq!(cu3.act_on(), 0b101);
+        assert!(cu3.is_equivalent(&op::u3(1.0, 2.0, 3.0, 0b100).c(0b001).unwrap(), 3, TOL));
+        assert!(op::cu3(0.0, 0.0, 0.0, 0b01, 0b10)
+            .unwrap()
+            .is_equivalent(&op::id(), 2, TOL));
+        assert!(op::cu3(1.0, 2.0, 3.0, 0b011, 0b010).is_none());
+    }
+
+    #[test]
+    fn fuse_single_qubit() {
+        const TOL: R = 10. * TOLERANCE;
+
+        let op = op::u3(0.3, 1.2, -0.7, 0b001)
+            * op::h(0b010)
+            * op::x(0b010)
+            * op::rx(0.5, 0b100)
+            * op::cnot(0b001, 0b010)
+            * op::t(0b001)
+            * op::s(0b001).dgr()
+            * op::swap(0b110)
+            * op::y(0b010)
+            * op::ry(2.0, 0b100)
+            * op::rz(0.1, 0b100);
+        let fused = op.clone().fuse_single_qubit();
+
+        assert!(fused.is_equivalent(&op, 3, TOL));
+        // u3 on q0, h*x on q1, rx on q2, cnot, t*s on q0, swap, y on q1, ry*rz on q2
+        assert_eq!(fused.len(), 8);
+        assert_eq!(fused.act_on(), op.act_on());
+
+        let op = op::x(0b101) * op::z(0b101) * op::rzz(0.4, 0b101);
+        assert_eq!(op.clone().fuse_single_qubit(), op);
+        assert_eq!(op::id().fuse_single_qubit(), op::id());
+    }
+
+    #[test]
+    fn optimize_schedule() {
+        use rand::prelude::*;
+
+        const Q_NUM: N = 5;
+        const TOL: R = 10. * TOLERANCE;
+
+        let op = op::h(0b001) * op::x(0b100) * op::cnot(0b001, 0b010) * op::t(0b100) * op::s(0b001);
+        assert_eq!(
+            op.clone().optimize_schedule(),
+            op::x(0b100) * op::t(0b100) * op::h(0b001) * op::cnot(0b001, 0b010) * op::s(0b001)
+        );
+
+        let mut rng = thread_rng();
+        for _ in 0..20 {
+            let op = (0..30).fold(op::id(), |op, _| {
+                let (a, b) = (1 << rng.gen_range(0..Q_NUM), 1 << rng.gen_range(0..Q_NUM));
+                let angle = rng.gen_range(-PI..PI);
+                op * match rng.gen_range(0..5) {
+                    0 => op::h(a),
+                    1 => op::rx(angle, a),
+                    2 => op::rz(angle, a),
+                    3 if a != b => op::cnot(a, b),
+                    4 if a != b => op::ryy(angle, a | b),
+                    _ => op::ry(angle, a),
+                }
+            });
+            let scheduled = op.clone().optimize_schedule();
+
+            assert_eq!(scheduled.len(), op.len());
+            assert!(scheduled.is_equivalent(&op, Q_NUM, TOL));
+        }
+    }
+
+    #[test]
+    fn ising_decomposed() {
+        const TOL: R = 10. * TOLERANCE;
+
+        for phase in [0.0, 0.7, -2.3, PI] {
+            for mask in [0b011, 0b101, 0b110] {
+                assert!(op::rxx_decomposed(phase, mask).is_equivalent(
+                    &op::rxx(phase, mask),
+                    3,
+                    TOL
+                ));
+                assert!(op::ryy_decomposed(phase, mask).is_equivalent(
+                    &op::ryy(phase, mask),
+                    3,
+                    TOL
+                ));
+                assert!(op::rzz_decomposed(phase, mask).is_equivalent(
+                    &op::rzz(phase, mask),
+                    3,
+                    TOL
+                ));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Mask should contain 2 bit!")]
+    fn ising_decomposed_wrong_mask() {
+        op::rzz_decomposed(1.0, 0b111);
+    }
+
+    #[test]
+    fn vec_of_single_ops() {
+        const TOL: R = 10. * TOLERANCE;
+
+        let op = op::h(0b011) * op::cnot(0b001, 0b100) * op::rx(0.7, 0b010) * op::t(0b100);
+        let gates = op.iter().cloned().collect::<Vec<SingleOp>>();
+
+        assert_eq!(gates.act_on(), op.act_on());
+        assert_eq!(gates.matrix(3), op.matrix(3));
+        assert_eq!(gates.clone().dgr().matrix(3), op.clone().dgr().matrix(3));
+        assert_eq!(
+            gates.clone().c(0b1000).unwrap().matrix(4),
+            op.clone().c(0b1000).unwrap().matrix(4)
+        );
+        assert!(gates.clone().c(0b0100).is_none());
+
+        let mut q1 = QReg::new(3);
+        q1.apply(&gates);
+        assert!(q1.approx_eq(&QReg::new(3).then(&op), TOL));
+        q1.apply(&gates.dgr());
+        assert!(q1.approx_eq(&QReg::new(3), TOL));
+    }
+
+    #[test]
+    fn apply_each() {
+        let v = VReg::new(5);
+        let odd = VReg::from(v[|i| i & 1 == 1]);
+
+        let op = op::apply_each(op::h, &odd);
+        assert_eq!(op.len(), 2);
+        assert_eq!(op, op::h(0b00010) * op::h(0b01000));
+
+        let op = op::apply_each(|q| op::rx(0.5, q) * op::z(q), &VReg::new(5));
+        assert_eq!(op.len(), 10);
+        let rx = (0..5).fold(op::id(), |op, i| op * op::rx(0.5, 1 << i));
+        assert!(op.is_equivalent(&(rx * op::z(0b11111)), 5, 10. * TOLERANCE));
+
+        assert_eq!(op::apply_each(op::x, &VReg::new(0)), op::id());
+    }
+
+    #[test]
+    fn repetition_code() {
+        const CODE: N = 0b00111;
+        const SYNDROME: N = 0b11000;
+
+        for (error, expected) in [(0, 0b00), (0b001, 0b01), (0b010, 0b11), (0b100, 0b10)] {
+            let mut q = QReg::new(5);
+            q.apply(&op::bit_flip_encode(0b001, 0b110));
+            if error != 0 {
+                q.apply(&op::x(error));
+            }
+            q.apply(&op::bit_flip_syndrome(CODE, SYNDROME));
+            assert_eq!(q.measure_mask(SYNDROME).get() >> 3, expected);
+
+            // syndrome is not affected by the encoded state
+            let mut q = QReg::new(5);
+            q.apply(&(op::ry(1.1, 0b001) * op::phase_flip_encode(0b001, 0b110)));
+            if error != 0 {
+                q.apply(&op::z(error));
+            }
+            q.apply(&op::phase_flip_syndrome(CODE, SYNDROME));
+            assert_eq!(q.measure_mask(SYNDROME).get() >> 3, expected);
+        }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Syndrome requires three code qubits and two distinct syndrome qubits!"
+    )]
+    fn repetition_code_overlap() {
+        op::bit_flip_syndrome(0b00111, 0b00110);
+    }
+
+    #[test]
+    fn control_block() {
+        const TOL: R = 10. * TOLERANCE;
+
+        let block = op::h(0b001) * op::ry(0.7, 0b010).c(0b001).unwrap();
+        let u = block.matrix(2);
+        let c_block = op::control_block(block.clone(), 0b100).unwrap();
+
+        let expected = (0..8)
+            .map(|row| {
+                (0..8)
+                    .map(|col| match (row & 0b100, col & 0b100) {
+                        (0b100, 0b100) => u[row & 0b011][col & 0b011],
+                        (0, 0) if row == col => C_ONE,
+                        _ => C_ZERO,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        c_block
+            .matrix(3)
+            .into_iter()
+            .flatten()
+            .zip(expected.into_iter().flatten())
+            .for_each(|(a, b)| assert!((a - b).norm() < TOL));
+
+        assert_eq!(c_block, block.clone().c(0b100).unwrap());
+        assert!(op::control_block(block, 0b010).is_none());
+    }
+
+    #[test]
+    fn unitary_with_tol() {
+        const TOL: R = 10. * TOLERANCE;
+
+        let h = C::from(FRAC_1_SQRT_2);
+        let h_off = h + TOLERANCE;
+
+        assert!(op::unitary([h, h, h, -h], 0b10).is_some());
+        assert!(op::unitary([h_off, h, h, -h], 0b10).is_none());
+        let op = op::unitary_with_tol([h_off, h, h, -h], 0b10, TOL).unwrap();
+        assert!(op.is_equivalent(&op::h(0b10), 2, TOL));
+        assert!(op::unitary_with_tol([h_off, h, h, -h], 0b10, 0.1 * TOLERANCE).is_none());
+        assert!(op::unitary_with_tol([h, h, h, -h], 0b11, TOL).is_none());
+
+        #[rustfmt::skip]
+        let swap = [
+            C_ONE, C_ZERO, C_ZERO, C_ZERO,
+            C_ZERO, C_ZERO, C_ONE, C_ZERO,
+            C_ZERO, C_ONE + TOLERANCE, C_ZERO, C_ZERO,
+            C_ZERO, C_ZERO, C_ZERO, C_ONE,
+        ];
+        assert!(op::unitary2(swap, 0b001, 0b100).is_none());
+        let op = op::unitary2_with_tol(swap, 0b001, 0b100, TOL).unwrap();
+        assert!(op.is_equivalent(&op::swap(0b101), 3, TOL));
+    }
+
+    #[test]
+    fn multiplex() {
+        const TOL: R = 10. * TOLERANCE;
+        const I: M1 = [C_ONE, C_ZERO, C_ZERO, C_ONE];
+        const X: M1 = [C_ZERO, C_ONE, C_ONE, C_ZERO];
+        const Z: M1 = [C_ONE, C_ZERO, C_ZERO, C { re: -1.0, im: 0.0 }];
+
+        let cnot = op::multiplex(0b01, 0b10, I, X).unwrap();
+        assert_eq!(cnot.act_on(), 0b11);
+        assert!(cnot.is_equivalent(&op::x(0b10).c(0b01).unwrap(), 2, TOL));
+
+        let op = op::multiplex(0b100, 0b001, X, Z).unwrap();
+        assert!(op.is_equivalent(
+            &(op::x(0b001).c(0b100).unwrap() * op::x(0b001) * op::z(0b001).c(0b100).unwrap()),
+            3,
+            TOL
+        ));
+
+        assert!(op::multiplex(0b01, 0b01, I, X).is_none());
+        assert!(op::multiplex(0b11, 0b100, I, X).is_none());
+        assert!(op::multiplex(0b01, 0b10, I, [C_ONE; 4]).is_none());
+    }
+
+    #[test]
+    fn grover_diffusion() {
+        let amplitudes = |q: &QReg| -> Vec<R> {
+            q.get_polar()
+                .into_iter()
+                .map(|(r, phi)| r * phi.cos())
+                .collect()
+        };
+
+        let mut q = QReg::new(3);
+        q.apply(&(op::ry(0.3, 0b001) * op::ry(1.1, 0b010) * op::ry(2.5, 0b100)));
+        let before = amplitudes(&q);
+        let mean = before.iter().sum::<R>() / before.len() as R;
+
+        q.apply(&op::grover_diffusion(0b111));
+        amplitudes(&q)
+            .into_iter()
+            .zip(before)
+            .for_each(|(after, before)| {
+                assert!((after - (before - 2.0 * mean)).abs() < 10. * TOLERANCE)
+            });
+
+        assert_eq!(op::grover_diffusion(0), op::id());
+        assert_eq!(op::grover_diffusion(0b1010).act_on(), 0b1010);
+    }
+
+    #[test]
+    fn qft_approx() {
+        let a_mask = 0b10110101;
+        assert_eq!(op::qft_approx(a_mask, 5), op::qft(a_mask));
+        assert_eq!(op::qft_approx(a_mask, 100), op::qft(a_mask));
+
+        // 5 Hadamard gates and controlled rotations with distance not more than cutoff
+        assert_eq!(op::qft(a_mask).len(), 5 + 4 + 3 + 2 + 1);
+        assert_eq!(op::qft_approx(a_mask, 2).len(), 5 + 2 + 2 + 2 + 1);
+        assert_eq!(op::qft_approx(a_mask, 0).len(), 5);
+
+        let q = QReg::with_state(5, 0b10011);
+        let (mut q_exact, mut q_approx) = (q.clone(), q);
+        q_exact.apply(&op::qft(0b11111));
+        q_approx.apply(&op::qft_approx(0b11111, 3));
+
+        let overlap = q_exact
+            .get_polar()
+            .into_iter()
+            .zip(q_approx.get_polar())
+            .map(|((r1, phi1), (r2, phi2))| C::from_polar(r1 * r2, phi2 - phi1))
+            .sum::<C>();
+        assert!(overlap.norm_sqr() > 0.99);
+    }
+
+    #[test]
+    fn light_cone() {
+        let op = op::h(0b000011)
+            * op::cnot(0b000001, 0b000010)
+            * op::barrier(0b111111)
+            * op::x(0b000100).c(0b001000).unwrap()
+            * op::rzz(0.5, 0b010010)
+            * op::x(0b100000)
+            * op::cnot(0b000001, 0b000100)
+            * op::id();
+
+        assert_eq!(op.light_cone(0b000001), 0b001111);
+        assert_eq!(op.light_cone(0b000010), 0b010011);
+        assert_eq!(op.light_cone(0b001000), 0b001100);
+        assert_eq!(op.light_cone(0b010100), 0b011111);
+
+        // isolated qubit is influenced only by itself
+        assert_eq!(op.light_cone(0b100000), 0b100000);
+        assert_eq!(op.light_cone(0), 0);
+        assert_eq!(op::id().light_cone(0b101), 0b101);
+    }
+
+    #[test]
+    fn zyz_decompose() {
+        let matrix = |op: &MultiOp| {
+            let m = op.matrix(1);
+            [m[0][0], m[0][1], m[1][0], m[1][1]]
+        };
+
+        let gates = [
+            op::h(0b1),
+            op::x(0b1),
+            op::y(0b1),
+            op::z(0b1),
+            op::s(0b1).dgr(),
+            op::t(0b1),
+            op::id(),
+            op::rx(0.3, 0b1),
+            op::u3(1.2, -0.4, 2.9, 0b1),
+            op::h(0b1) * op::t(0b1) * op::ry(2.2, 0b1) * op::h(0b1),
+        ];
+        for gate in &gates {
+            let mut m = matrix(gate);
+            m.iter_mut().for_each(|z| *z *= C::from_polar(1.0, 0.7));
+
+            let zyz = op::zyz_decompose(m, 0b1).unwrap();
+            assert_eq!(zyz.len(), 3);
+            assert!(zyz.is_equivalent(gate, 1, 10. * TOLERANCE));
+            assert_eq!(op::zyz_decompose(m, 0b100).unwrap().act_on(), 0b100);
+        }
+
+        assert!(op::zyz_decompose([C_ONE, C_ONE, C_ZERO, C_ONE], 0b1).is_none());
+        assert!(op::zyz_decompose(matrix(&op::h(0b1)), 0b11).is_none());
+    }
+
+    #[test]
+    fn controlled_qft() {
+        let qft = op::qft(0b0111);
+        assert!(qft.iter().any(|op| op.ctrl() != 0));
+
+        let cqft = op::controlled_qft(0b1000, 0b0111).unwrap();
+        assert_eq!(cqft.len(), qft.len());
+
+        let (matrix, qft) = (cqft.matrix(4), qft.matrix(3));
+        for (idx, row) in matrix.iter().enumerate() {
+            for (jdx, z) in row.iter().enumerate() {
+                let expected = match (idx & 0b1000, jdx & 0b1000) {
+                    (0, 0) => C::from((idx == jdx) as u8 as R),
+                    (0b1000, 0b1000) => qft[idx & 0b0111][jdx & 0b0111],
+                    _ => C_ZERO,
+                };
+                assert!((z - expected).norm() < TOLERANCE);
+            }
+        }
+
+        // controls are added to already controlled gates, but should not repeat them
+        assert_eq!(
+            op::x(0b100).c(0b001).unwrap().c(0b010),
+            op::x(0b100).c(0b011),
+        );
+        assert!(op::x(0b10).c(0b01).unwrap().c(0b01).is_none());
+        assert!(op::controlled_qft(0b0001, 0b0111).is_none());
+        assert!(op::controlled_qft(0b1010, 0b0111).is_none());
+    }
+
+    #[test]
+    fn mcrz() {
+        let (ctrl, target) = (0b101, 0b010);
+        let mcrz = op::mcrz(1.0, ctrl, target).unwrap();
+        assert_eq!(mcrz.len(), 1);
+
+        // Only the subspace with all control qubits set is rotated
+        let matrix = mcrz[0].to_matrix(3);
+        let rz = op::rz(1.0, 0b1)[0].to_matrix(1);
+        for (i, row) in matrix.iter().enumerate() {
+            for (j, z) in row.iter().enumerate() {
+                let expected = if i & ctrl != ctrl || j & ctrl != ctrl {
+                    if i == j {
+                        C_ONE
+                    } else {
+                        C_ZERO
+                    }
+                } else if i & !target == j & !target {
+                    rz[(i & target != 0) as N][(j & target != 0) as N]
+                } else {
+                    C_ZERO
+                };
+                assert!((z - expected).norm() < TOLERANCE);
+            }
+        }
+
+        assert!(op::mcrz(1.0, 0b11, 0b01).is_none());
+        assert!(op::mcrz(1.0, 0b100, 0b011).is_none());
+        assert_eq!(op::mcrz(1.0, 0, 0b1), Some(op::rz(1.0, 0b1)));
+    }
+
+    #[test]
+    fn phase_estimation() {
+        // Controlled phase exp(2πi * k/16) is read out exactly by 4 counting qubits
+        for k in 0..16 {
+            let unitary = op::rz(4.0 * PI * k as R / 16.0, 0b00001);
+            let qpe = op::phase_estimation(0b11110, 0b00001, &unitary).unwrap();
+
+            let mut q = QReg::with_state(5, 0b00001);
+            q.apply(&qpe);
+            assert!((q.get_probabilities()[k << 1 | 1] - 1.0).abs() < 10. * TOLERANCE);
+        }
+
+        // Phase of ipea_3_pi_8.qasm
+        let unitary = op::rz(0.75 * PI, 0b100000);
+        let qpe = op::phase_estimation(0b011110, 0b100000, &unitary).unwrap();
+        let mut q = QReg::with_state(6, 0b100000);
+        q.apply(&qpe);
+        assert_eq!(q.measure_mask(0b011110).get(), 3 << 1);
+
+        let unitary = op::x(0b01);
+        assert!(op::phase_estimation(0b11, 0b01, &unitary).is_none());
+        assert!(op::phase_estimation(0b10, 0b00, &unitary).is_none());
+    }
+
     #[test]
     fn ends_with() {
         let op = (
@@ -2,6 +2,10 @@ use super::*;
 use crate::{math::consts::PI, operator::single::rotate::rz};
 
 pub fn qft(a_mask: N) -> MultiOp {
+    qft_approx(a_mask, N::MAX)
+}
+
+pub fn qft_approx(a_mask: N, cutoff: N) -> MultiOp {
     let count = a_mask.count_ones() as usize;
     match count {
         0 => MultiOp::default(),
@@ -19,8 +23,8 @@ pub fn qft(a_mask: N) -> MultiOp {
 
             for i in 0..(count - 1) {
                 res.append(&mut h::h(vec[i]));
-                res.extend((1..(count - i)).map(|j| {
-                    rz(vec[i + j], PI * 0.5f64.powi(j as i32))
+                res.extend((1..(count - i)).take(cutoff).map(|j| {
+                    rz(vec[i + j], PI * R::powi(0.5, j as i32))
                         .and_then(|op| op.c(vec[i]))
                         .unwrap()
                 }));
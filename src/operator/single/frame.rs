@@ -0,0 +1,143 @@
+use super::SingleOp;
+use crate::{
+    math::{bits_iter::BitsIter, consts::*, types::*},
+    operator::atomic::{AtomicOp, AtomicOpDispatch as Dispatch},
+};
+
+/// Pending Pauli operator ```i^i_pow X^x Z^z```, which should be applied after the stored state.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct PauliFrame {
+    pub x: N,
+    pub z: N,
+    pub i_pow: N,
+}
+
+/// What to do with a gate, passed through the [`PauliFrame`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum FrameUpdate {
+    /// Pauli gate was merged into the frame, so it should not be applied.
+    Absorbed,
+    /// Frame was commuted through the gate, so gate should be applied to the stored state.
+    Conjugated,
+    /// Gate does not map the frame to a Pauli operator, so the frame should be materialized first.
+    Blocked,
+}
+
+fn parity(mask: N) -> bool {
+    mask.count_ones() & 1 == 1
+}
+
+fn swap_bits(mask: N, a: N, b: N) -> N {
+    if (mask & a == 0) != (mask & b == 0) {
+        mask ^ a ^ b
+    } else {
+        mask
+    }
+}
+
+impl PauliFrame {
+    pub fn is_identity(&self) -> bool {
+        self.x == 0 && self.z == 0 && self.i_pow & 3 == 0
+    }
+
+    /// Amplitude of state *idx* after the frame is applied to *psi*.
+    #[inline]
+    pub fn amplitude(&self, psi: &[C], idx: N) -> C {
+        let mut i_pow = self.i_pow;
+        if parity((idx ^ self.x) & self.z) {
+            i_pow ^= 2;
+        }
+        crate::math::rotate(psi[idx ^ self.x], i_pow)
+    }
+
+    /// Either merge Pauli gate *op* into the frame,
+    /// or replace the frame ```P``` with ```U P U†``` for Clifford gate ```U```,
+    /// or leave the frame unchanged, if it commutes with *op*.
+    pub fn update(&mut self, op: &SingleOp) -> FrameUpdate {
+        let (act, ctrl) = (op.act, op.ctrl);
+        match (&op.func, ctrl.count_ones()) {
+            (Dispatch::Id(_), _) | (Dispatch::Barrier(_), _) => FrameUpdate::Conjugated,
+            (Dispatch::X(_), 0) => {
+                self.x ^= act;
+                FrameUpdate::Absorbed
+            }
+            (Dispatch::Z(_), 0) => {
+                if parity(act & self.x) {
+                    self.i_pow ^= 2;
+                }
+                self.z ^= act;
+                FrameUpdate::Absorbed
+            }
+            (Dispatch::Y(_), 0) => {
+                // Y = iXZ for every qubit
+                self.i_pow += act.count_ones() as N;
+                if parity(act & self.x) {
+                    self.i_pow ^= 2;
+                }
+                self.x ^= act;
+                self.z ^= act;
+                self.i_pow &= 3;
+                FrameUpdate::Absorbed
+            }
+            (Dispatch::H1(_), 0) => {
+                let (x, z) = (self.x & act, self.z & act);
+                if parity(x & z) {
+                    self.i_pow ^= 2;
+                }
+                self.x = self.x & !act | z;
+                self.z = self.z & !act | x;
+                FrameUpdate::Conjugated
+            }
+            (Dispatch::S(s), 0) => {
+                // S X S† = iXZ, S† X S = -iXZ
+                let x = self.x & act;
+                let n = x.count_ones() as N;
+                let dagger = s.diagonal(act & act.wrapping_neg()) != Some(C_IMAG);
+                self.i_pow = (self.i_pow + if dagger { 3 * n } else { n }) & 3;
+                self.z ^= x;
+                FrameUpdate::Conjugated
+            }
+            (Dispatch::Swap(_), 0) => {
+                let a = act & act.wrapping_neg();
+                let b = act ^ a;
+                self.x = swap_bits(self.x, a, b);
+                self.z = swap_bits(self.z, a, b);
+                FrameUpdate::Conjugated
+            }
+            (Dispatch::X(_), 1) => {
+                if self.x & ctrl != 0 {
+                    self.x ^= act;
+                }
+                if parity(self.z & act) {
+                    self.z ^= ctrl;
+                }
+                FrameUpdate::Conjugated
+            }
+            (Dispatch::Z(_), 1) => {
+                for target in BitsIter::from(act) {
+                    let (x_c, x_t) = (self.x & ctrl != 0, self.x & target != 0);
+                    if x_c {
+                        self.z ^= target;
+                    }
+                    if x_t {
+                        self.z ^= ctrl;
+                    }
+                    if x_c && x_t {
+                        self.i_pow ^= 2;
+                    }
+                }
+                FrameUpdate::Conjugated
+            }
+            (func, _) => {
+                let act_on = act | ctrl;
+                if act_on & (self.x | self.z) == 0
+                    || (act_on & self.x == 0 && func.diagonal(0).is_some())
+                {
+                    FrameUpdate::Conjugated
+                } else {
+                    FrameUpdate::Blocked
+                }
+            }
+        }
+    }
+}
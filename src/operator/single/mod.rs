@@ -1,5 +1,8 @@
 pub use super::Applicable;
-use crate::{math::types::*, operator::atomic::*};
+use crate::{
+    math::types::*,
+    operator::{atomic::*, Noise},
+};
 
 macro_rules! single_op_checked {
     ($op:expr) => {
@@ -10,10 +13,18 @@ macro_rules! single_op_checked {
     };
 }
 
+mod frame;
 pub mod pauli;
 pub mod rotate;
 pub mod swap;
 
+pub(crate) use frame::{FrameUpdate, PauliFrame};
+
+#[inline(always)]
+pub fn barrier(a_mask: N) -> SingleOp {
+    super::atomic::barrier::Op::new(a_mask).into()
+}
+
 /// Single quantum operation.
 ///
 /// This structure represents the unit of computation for quantum simulator.
@@ -78,6 +89,186 @@ impl SingleOp {
             self.func.name()
         }
     }
+
+    /// Return dense matrix representation of quantum gate on *q_num* qubits.
+    /// Matrix is indexed as ```matrix[row][column]```, where column *j* is the result
+    /// of applying gate to the basis state |j>.
+    ///
+    /// This is useful to verify custom gates or pass them to linear algebra tools.
+    /// Note, that matrix has 2<sup>q_num</sup> &times; 2<sup>q_num</sup> elements,
+    /// so it is only feasible for small number of qubits.
+    /// Gate should act only on the first *q_num* qubits.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let single_op = &op::x(0b1)[0];
+    /// let matrix = single_op.to_matrix(1);
+    ///
+    /// assert_eq!(matrix[0][1].re, 1.0);
+    /// assert_eq!(matrix[1][0].re, 1.0);
+    /// ```
+    pub fn to_matrix(&self, q_num: N) -> Vec<Vec<C>> {
+        self.matrix(q_num)
+    }
+
+    /// Return matrix of quantum gate on the qubits it acts on, including control qubits.
+    ///
+    /// Unlike [`to_matrix`](SingleOp::to_matrix), the size of matrix does not depend on positions of qubits:
+    /// it has 2<sup>k</sup> &times; 2<sup>k</sup> elements, where ```k = act_on().count_ones()```,
+    /// e.g. 2&times;2 for single qubit gate and 4&times;4 for two-qubit or controlled single qubit gate.
+    /// Qubits are enumerated from the lowest one, which corresponds to the lowest bit of row or column index.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let cnot = &op::cnot(0b1000, 0b0010)[0];
+    /// let matrix = cnot.local_matrix();
+    ///
+    /// assert_eq!(matrix.len(), 4);
+    /// // control is the upper local qubit: |10> <-> |11>
+    /// assert_eq!(matrix[0b11][0b10].re, 1.0);
+    /// ```
+    pub fn local_matrix(&self) -> Vec<Vec<C>> {
+        let all = self.act_on();
+        let compress = |mask: N| {
+            crate::math::bits_iter::BitsIter::from(all)
+                .enumerate()
+                .fold(
+                    0,
+                    |acc, (idx, bit)| {
+                        if mask & bit != 0 {
+                            acc | 1 << idx
+                        } else {
+                            acc
+                        }
+                    },
+                )
+        };
+
+        let local = Self {
+            act: compress(self.act),
+            ctrl: compress(self.ctrl),
+            func: self.func.clone().remap(&compress),
+        };
+        local.matrix(all.count_ones() as N)
+    }
+
+    pub(crate) fn matrix_m1(&self) -> Option<M1> {
+        if self.ctrl != 0 {
+            None
+        } else {
+            self.func.matrix_m1()
+        }
+    }
+
+    /// Name of gate without masks and parameters, with one ```C``` per control qubit, e.g. ```CCX``` or ```RZ```.
+    pub(crate) fn kind(&self) -> String {
+        let name = self.func.name();
+        let prefix = name
+            .split(|c: char| c.is_ascii_digit())
+            .next()
+            .unwrap_or_default();
+
+        let mut kind = "C".repeat(self.ctrl.count_ones() as usize) + prefix;
+        kind.extend(std::iter::repeat_n(')', prefix.matches('(').count()));
+        kind
+    }
+
+    pub(crate) fn ctrl(&self) -> N {
+        self.ctrl
+    }
+
+    pub(crate) fn is_identity(&self) -> bool {
+        matches!(
+            self.func,
+            dispatch::AtomicOpDispatch::Id(_) | dispatch::AtomicOpDispatch::Barrier(_)
+        )
+    }
+
+    pub(crate) fn is_diagonal(&self) -> bool {
+        self.func.diagonal(0).is_some()
+    }
+
+    pub(crate) fn diagonal(&self, idx: N) -> Option<C> {
+        let phase = self.func.diagonal(idx)?;
+        if !idx & self.ctrl == 0 {
+            Some(phase)
+        } else {
+            Some(C { re: 1.0, im: 0.0 })
+        }
+    }
+
+    pub(crate) fn to_qasm(&self, q_reg: &str) -> Option<String> {
+        // Gates of qelib1.inc, which could be emitted, with control prefixes
+        const QELIB1: &[&str] = &[
+            "x", "y", "z", "h", "s", "sdg", "t", "tdg", "rx", "ry", "rz", "swap", "rxx", "rzz",
+            "cx", "cy", "cz", "ch", "crx", "cry", "crz", "cswap", "ccx",
+        ];
+        // Gates, which are applied to every target qubit independently
+        const SINGLE_QUBIT: &[&str] =
+            &["x", "y", "z", "h", "s", "sdg", "t", "tdg", "rx", "ry", "rz"];
+
+        let qubits = |mask: N| {
+            crate::math::bits_iter::BitsIter::from(mask)
+                .map(move |q| format!("{}[{}]", q_reg, q.trailing_zeros()))
+        };
+
+        let name = self.func.qasm()?;
+        if matches!(self.func, dispatch::AtomicOpDispatch::Barrier(_)) {
+            return Some(
+                qubits(self.act)
+                    .map(|q| format!("{} {};", name, q))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            );
+        }
+
+        if matches!(name.as_str(), "s" | "sdg" | "t" | "tdg") && self.ctrl.count_ones() == 1 {
+            // There is no controlled phase gate in qelib1.inc, so it is emitted as controlled RZ
+            // followed by a phase on the control qubit
+            let phase = self
+                .func
+                .diagonal(self.act & self.act.wrapping_neg())?
+                .arg();
+            let ctrl = qubits(self.ctrl).next()?;
+            return Some(
+                qubits(self.act)
+                    .map(|q| {
+                        format!(
+                            "crz({}) {}, {};\nu1({}) {};",
+                            phase,
+                            ctrl,
+                            q,
+                            0.5 * phase,
+                            ctrl
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            );
+        }
+
+        let base = name.split('(').next().unwrap_or_default();
+        let prefix = "c".repeat(self.ctrl.count_ones() as N);
+        if !QELIB1.contains(&format!("{}{}", prefix, base).as_str()) {
+            return None;
+        }
+
+        let targets: Vec<Vec<_>> = if SINGLE_QUBIT.contains(&base) {
+            qubits(self.act).map(|q| vec![q]).collect()
+        } else {
+            vec![qubits(self.act).collect()]
+        };
+        Some(
+            targets
+                .into_iter()
+                .map(|target| {
+                    let args = qubits(self.ctrl).chain(target).collect::<Vec<_>>();
+                    format!("{}{} {};", prefix, name, args.join(", "))
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+    }
 }
 
 impl Applicable for SingleOp {
@@ -92,6 +283,21 @@ impl Applicable for SingleOp {
         self.func.for_each_par(psi_i, &mut psi_o[..], ctrl);
     }
 
+    fn apply_noisy(&self, psi_i: &[C], psi_o: &mut Vec<C>, noise: &dyn Noise) {
+        self.apply(psi_i, psi_o);
+        if !matches!(self.func, dispatch::AtomicOpDispatch::Barrier(_)) {
+            noise.apply(self.act_on(), psi_o);
+        }
+    }
+
+    #[cfg(feature = "multi-thread")]
+    fn apply_sync_noisy(&self, psi_i: &[C], psi_o: &mut Vec<C>, noise: &dyn Noise) {
+        self.apply_sync(psi_i, psi_o);
+        if !matches!(self.func, dispatch::AtomicOpDispatch::Barrier(_)) {
+            noise.apply(self.act_on(), psi_o);
+        }
+    }
+
     #[inline]
     fn act_on(&self) -> N {
         self.act | self.ctrl
@@ -105,6 +311,10 @@ impl Applicable for SingleOp {
         }
     }
 
+    fn gates(&self) -> Option<Box<dyn Iterator<Item = &SingleOp> + '_>> {
+        Some(Box::new(std::iter::once(self)))
+    }
+
     #[inline(always)]
     fn c(self, c: N) -> Option<Self> {
         if self.act_on() & c != 0 {
@@ -137,6 +347,7 @@ impl std::fmt::Debug for SingleOp {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::math::consts::TOLERANCE;
 
     #[test]
     fn name() {
@@ -149,6 +360,117 @@ mod tests {
         assert_eq!(format!("{:?}", single_op), format!("C4_X123"));
     }
 
+    #[test]
+    fn kind() {
+        assert_eq!(pauli::x(123).kind(), "X");
+        assert_eq!(pauli::x(0b100).c(0b011).unwrap().kind(), "CCX");
+        assert_eq!(
+            rotate::rz(0b10, 1.5).unwrap().c(0b01).unwrap().kind(),
+            "CRZ"
+        );
+        assert_eq!(crate::operator::sqrt_swap(0b11)[0].kind(), "sqrt(SWAP)");
+    }
+
+    #[test]
+    fn to_matrix() {
+        const O: C = C { re: 0.0, im: 0.0 };
+        const I: C = C { re: 1.0, im: 0.0 };
+
+        let single_op = pauli::x(0b10).c(0b01).unwrap();
+        assert_eq!(
+            single_op.to_matrix(2),
+            [[I, O, O, O], [O, O, O, I], [O, O, I, O], [O, I, O, O]]
+        );
+        assert_eq!(single_op.to_matrix(2), single_op.matrix(2));
+    }
+
+    #[test]
+    fn matrix_m1() {
+        const EPS: R = TOLERANCE;
+
+        let ops = [
+            pauli::x(0b1),
+            pauli::y(0b1),
+            pauli::z(0b1),
+            pauli::s(0b1),
+            pauli::s(0b1).dgr(),
+            pauli::t(0b1),
+            pauli::t(0b1).dgr(),
+            crate::operator::h(0b1)[0].clone(),
+            rotate::rx(0b1, 0.7).unwrap(),
+            rotate::ry(0b1, 1.3).unwrap(),
+            rotate::rz(0b1, -2.1).unwrap(),
+            pauli::u1(0b1, rotate::ry(0b1, 0.4).unwrap().matrix_m1().unwrap()).unwrap(),
+        ];
+
+        for op in ops {
+            let u = op.matrix_m1().unwrap();
+            let matrix = op.to_matrix(1);
+            for (idx, z) in u.iter().enumerate() {
+                assert!((z - matrix[idx >> 1][idx & 1]).norm() < EPS, "{:?}", op);
+            }
+        }
+
+        assert_eq!(pauli::x(0b11).matrix_m1(), None);
+        assert_eq!(pauli::x(0b10).c(0b01).unwrap().matrix_m1(), None);
+        assert_eq!(rotate::rzz(0b11, 1.0).unwrap().matrix_m1(), None);
+    }
+
+    #[test]
+    fn local_matrix() {
+        use crate::math::consts::FRAC_1_SQRT_2;
+
+        const O: C = C { re: 0.0, im: 0.0 };
+        const ANGLE: R = 1.23456;
+
+        let h = C::from(FRAC_1_SQRT_2);
+        let op = crate::operator::h(0b1000)[0].clone();
+        assert_eq!(op.local_matrix(), [[h, h], [h, -h]]);
+
+        let (cos, i_sin) = (
+            C::from((0.5 * ANGLE).cos()),
+            C::new(0.0, (0.5 * ANGLE).sin()),
+        );
+        let op = rotate::ryy(0b100100, ANGLE).unwrap();
+        assert_eq!(
+            op.local_matrix(),
+            [
+                [cos, O, O, i_sin],
+                [O, cos, -i_sin, O],
+                [O, -i_sin, cos, O],
+                [i_sin, O, O, cos]
+            ]
+        );
+        assert_eq!(
+            op.local_matrix(),
+            rotate::ryy(0b11, ANGLE).unwrap().to_matrix(2)
+        );
+
+        let op = pauli::u2(0b1000, 0b0010, {
+            let mut u = [O; 16];
+            [0, 2, 1, 3]
+                .iter()
+                .enumerate()
+                .for_each(|(i, &j)| u[4 * i + j] = C::from(1.0));
+            u
+        })
+        .unwrap()
+        .c(0b0100)
+        .unwrap();
+        let local = op.local_matrix();
+        assert_eq!(local.len(), 8);
+        assert_eq!(op.to_matrix(4).len(), 16);
+        for (idx, row) in local.iter().enumerate() {
+            for (jdx, z) in row.iter().enumerate() {
+                let (idx4, jdx4) = (
+                    (idx & 1) << 1 | (idx & 2) << 1 | (idx & 4) << 1,
+                    (jdx & 1) << 1 | (jdx & 2) << 1 | (jdx & 4) << 1,
+                );
+                assert_eq!(*z, op.to_matrix(4)[idx4][jdx4]);
+            }
+        }
+    }
+
     #[test]
     fn wrong_ctrl_mask() {
         let op = rotate::ryy(0b101, 1.35).unwrap();
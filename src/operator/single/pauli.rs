@@ -37,3 +37,19 @@ pub fn u1(a_mask: N, matrix: M1) -> Option<SingleOp> {
 pub fn u2(a_mask: N, b_mask: N, matrix: M2) -> Option<SingleOp> {
     single_op_checked!(atomic::u2::Op::new(a_mask, b_mask, matrix))
 }
+
+#[inline(always)]
+pub fn u1_eps(a_mask: N, matrix: M1, eps: R) -> Option<SingleOp> {
+    match atomic::u1::Op::new(a_mask, matrix) {
+        op if op.is_valid_eps(eps) => Some(op.into()),
+        _ => None,
+    }
+}
+
+#[inline(always)]
+pub fn u2_eps(a_mask: N, b_mask: N, matrix: M2, eps: R) -> Option<SingleOp> {
+    match atomic::u2::Op::new(a_mask, b_mask, matrix) {
+        op if op.is_valid_eps(eps) => Some(op.into()),
+        _ => None,
+    }
+}
@@ -0,0 +1,173 @@
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    math::types::*,
+    operator::{multi::apply_sequence, Applicable, MultiOp, Noise, SingleOp},
+};
+
+type Logger = Arc<dyn Fn(&str, &str, Duration) + Send + Sync>;
+
+/// Quantum operation, which reports every applied gate.
+///
+/// [`TracedOp`] is produced by [`op::traced`](crate::operator::traced).
+/// It is applied exactly as the wrapped [`MultiOp`], but after each gate
+/// the logger is called with the label of operation, the [`name`](SingleOp::name) of gate and elapsed time.
+/// Logger should be set with [`with_logger`](TracedOp::with_logger), otherwise nothing is reported.
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// # use std::sync::{Arc, Mutex};
+/// let names = Arc::new(Mutex::new(vec![]));
+/// let log = names.clone();
+/// let bell = op::traced(op::h(0b01) * op::cnot(0b01, 0b10), "bell")
+///     .with_logger(move |_, name, _| log.lock().unwrap().push(name.to_string()));
+///
+/// let mut q = QReg::new(2);
+/// q.apply(&bell);
+///
+/// assert_eq!(names.lock().unwrap().len(), 2);
+/// ```
+#[derive(Clone)]
+pub struct TracedOp {
+    inner: MultiOp,
+    label: String,
+    logger: Option<Logger>,
+}
+
+impl TracedOp {
+    pub(crate) fn new(inner: MultiOp, label: &str) -> Self {
+        Self {
+            inner,
+            label: label.to_string(),
+            logger: None,
+        }
+    }
+
+    /// Replace logger, which is called with the label of operation, name of gate and elapsed time.
+    pub fn with_logger<F>(self, logger: F) -> Self
+    where
+        F: Fn(&str, &str, Duration) + Send + Sync + 'static,
+    {
+        Self {
+            logger: Some(Arc::new(logger)),
+            ..self
+        }
+    }
+
+    /// Return wrapped operation.
+    pub fn inner(&self) -> &MultiOp {
+        &self.inner
+    }
+
+    fn apply_traced<F>(&self, psi_i: &[C], psi_o: &mut Vec<C>, mut apply: F)
+    where
+        F: FnMut(&SingleOp, &[C], &mut Vec<C>),
+    {
+        let logger = match &self.logger {
+            Some(logger) => logger,
+            None => return apply_sequence(self.inner.iter(), psi_i, psi_o, apply),
+        };
+        apply_sequence(self.inner.iter(), psi_i, psi_o, |op, psi_i, psi_o| {
+            let start = Instant::now();
+            apply(op, psi_i, psi_o);
+            logger(&self.label, &op.name(), start.elapsed());
+        });
+    }
+}
+
+impl fmt::Debug for TracedOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {:?}", self.label, self.inner)
+    }
+}
+
+impl Applicable for TracedOp {
+    fn apply(&self, psi_i: &[C], psi_o: &mut Vec<C>) {
+        self.apply_traced(psi_i, psi_o, SingleOp::apply);
+    }
+
+    #[cfg(feature = "multi-thread")]
+    fn apply_sync(&self, psi_i: &[C], psi_o: &mut Vec<C>) {
+        self.apply_traced(psi_i, psi_o, SingleOp::apply_sync);
+    }
+
+    fn act_on(&self) -> N {
+        self.inner.act_on()
+    }
+
+    fn apply_noisy(&self, psi_i: &[C], psi_o: &mut Vec<C>, noise: &dyn Noise) {
+        self.apply_traced(psi_i, psi_o, |op, psi_i, psi_o| {
+            op.apply_noisy(psi_i, psi_o, noise)
+        });
+    }
+
+    #[cfg(feature = "multi-thread")]
+    fn apply_sync_noisy(&self, psi_i: &[C], psi_o: &mut Vec<C>, noise: &dyn Noise) {
+        self.apply_traced(psi_i, psi_o, |op, psi_i, psi_o| {
+            op.apply_sync_noisy(psi_i, psi_o, noise)
+        });
+    }
+
+    fn dgr(self) -> Self {
+        Self {
+            inner: self.inner.dgr(),
+            ..self
+        }
+    }
+
+    fn c(self, c_mask: N) -> Option<Self> {
+        Some(Self {
+            inner: self.inner.c(c_mask)?,
+            ..self
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use crate::prelude::*;
+
+    #[test]
+    fn same_state() {
+        let circuit = op::qft(0b1111) * op::h(0b0101) * op::rxx(0.3, 0b1001);
+
+        let log = std::sync::Arc::new(Mutex::new(vec![]));
+        let logger = log.clone();
+        let traced = op::traced(circuit.clone(), "circuit").with_logger(move |label, name, _| {
+            logger.lock().unwrap().push(format!("{label}: {name}"))
+        });
+        assert_eq!(traced.act_on(), 0b1111);
+
+        let mut q = QReg::with_state(4, 0b0110);
+        let mut q_traced = q.clone();
+        q.apply(&circuit);
+        q_traced.apply(&traced);
+        assert_eq!(q.get_polar(), q_traced.get_polar());
+
+        // without logger gates are applied silently
+        let mut q_silent = QReg::with_state(4, 0b0110);
+        q_silent.apply(&op::traced(circuit.clone(), "silent"));
+        assert_eq!(q.get_polar(), q_silent.get_polar());
+
+        let log = log.lock().unwrap().clone();
+        assert_eq!(log.len(), circuit.len());
+        assert_eq!(log[0], format!("circuit: {}", circuit[0].name()));
+
+        let traced = traced.dgr().c(0b10000).unwrap();
+        assert_eq!(traced.inner(), &circuit.dgr().c(0b10000).unwrap());
+
+        #[cfg(feature = "multi-thread")]
+        if let Some(mut q_sync) = QReg::with_state(4, 0b0110).num_threads(2) {
+            q_sync.apply(&op::traced(op::qft(0b1111), "sync").with_logger(|_, _, _| {}));
+            let mut q = QReg::with_state(4, 0b0110);
+            q.apply(&op::qft(0b1111));
+            assert!(q.approx_eq(&q_sync, crate::math::consts::TOLERANCE));
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
+
+use super::{Ast, Result};
+
+/// Cache of parsed syntax trees, keyed by hash of the source.
+///
+/// Parsing of the source, which is identical to one of already parsed ones, skips lexing and parsing
+/// and returns stored [`Ast`]. This is useful, when the same OpenQASM code is processed repeatedly, e.g. in loops.
+/// Since [`Ast`] borrows its source, every source should outlive the cache.
+/// Sources, which failed to parse, are not cached.
+///
+/// ```rust
+/// # use qvnt::qasm::ast::Cache;
+/// let mut cache = Cache::default();
+///
+/// let sources = vec![String::from("OPENQASM 2.0; qreg q[2]; h q[0]; cx q[0], q[1];"); 10];
+/// for source in &sources {
+///     cache.parse(source).unwrap();
+/// }
+///
+/// assert_eq!(cache.len(), 1);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Cache<'t> {
+    storage: HashMap<u64, Ast<'t>>,
+}
+
+impl<'t> Cache<'t> {
+    fn hash(source: &str) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        source.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Return syntax tree of the *source*, parsing it only if identical source was not parsed before.
+    pub fn parse(&mut self, source: &'t str) -> Result<'t, &Ast<'t>> {
+        let hash = Self::hash(source);
+        match self.storage.get(&hash) {
+            Some(ast) if ast.source() == source => {}
+            _ => {
+                let ast = Ast::from_source(source)?;
+                self.storage.insert(hash, ast);
+            }
+        }
+        Ok(&self.storage[&hash])
+    }
+
+    /// Number of cached syntax trees.
+    pub fn len(&self) -> usize {
+        self.storage.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.storage.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.storage.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cached_source() {
+        let first = String::from("OPENQASM 2.0; qreg q[2]; h q[0]; cx q[0], q[1];");
+        let second = first.clone();
+        let other = String::from("OPENQASM 2.0; qreg q[1]; x q[0];");
+
+        let mut cache = Cache::default();
+        assert!(cache.is_empty());
+
+        let ast = cache.parse(&first).unwrap().clone();
+        assert_eq!(ast, Ast::from_source(&first).unwrap());
+
+        // identical source is served from cache, so tree still refers to the first string
+        let cached = cache.parse(&second).unwrap();
+        assert_eq!(cached, &ast);
+        assert!(std::ptr::eq(cached.source(), first.as_str()));
+        assert_eq!(cache.len(), 1);
+
+        assert!(!std::ptr::eq(
+            cache.parse(&other).unwrap().source(),
+            first.as_str()
+        ));
+        assert_eq!(cache.len(), 2);
+
+        assert!(cache.parse("").is_err());
+        assert_eq!(cache.len(), 2);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
@@ -1,6 +1,8 @@
-use qasm::{self, AstNode};
+use qasm::{self, Argument, AstNode};
 
+mod cache;
 mod error;
+pub use cache::Cache;
 pub use error::*;
 
 #[derive(Clone, Debug, PartialEq)]
@@ -30,6 +32,95 @@ impl<'t> Ast<'t> {
     pub(crate) fn iter(&self) -> impl Iterator<Item = &AstNode<'t>> {
         self.ast.iter()
     }
+
+    /// Generate canonical OPENQASM 2.0 source from syntax tree.
+    ///
+    /// Comments and formatting of the original source are not preserved,
+    /// and included files appear in the output as their content.
+    /// Parsing of the generated source results in the same syntax tree.
+    pub fn to_source(&self) -> String {
+        let mut source = String::from("OPENQASM 2.0;\n");
+        for node in &self.ast {
+            write_node(&mut source, node);
+            source.push('\n');
+        }
+        source
+    }
+}
+
+fn write_arg(source: &mut String, arg: &Argument) {
+    match arg {
+        Argument::Qubit(alias, idx) => source.push_str(&format!("{alias}[{idx}]")),
+        Argument::Register(alias) => source.push_str(alias),
+    }
+}
+
+fn write_args(source: &mut String, args: &[Argument]) {
+    for (idx, arg) in args.iter().enumerate() {
+        if idx != 0 {
+            source.push_str(", ");
+        }
+        write_arg(source, arg);
+    }
+}
+
+fn write_gate(source: &mut String, name: &str, params: &[&str]) {
+    source.push_str(name);
+    if !params.is_empty() {
+        source.push_str(&format!("({})", params.join(", ")));
+    }
+    source.push(' ');
+}
+
+fn write_node(source: &mut String, node: &AstNode) {
+    match node {
+        AstNode::QReg(alias, size) => source.push_str(&format!("qreg {alias}[{size}];")),
+        AstNode::CReg(alias, size) => source.push_str(&format!("creg {alias}[{size}];")),
+        AstNode::Barrier(arg) => {
+            source.push_str("barrier ");
+            write_arg(source, arg);
+            source.push(';');
+        }
+        AstNode::Reset(arg) => {
+            source.push_str("reset ");
+            write_arg(source, arg);
+            source.push(';');
+        }
+        AstNode::Measure(q_arg, c_arg) => {
+            source.push_str("measure ");
+            write_arg(source, q_arg);
+            source.push_str(" -> ");
+            write_arg(source, c_arg);
+            source.push(';');
+        }
+        AstNode::ApplyGate(name, args, params) => {
+            write_gate(source, name, params);
+            write_args(source, args);
+            source.push(';');
+        }
+        AstNode::Opaque(name, args, params) => {
+            source.push_str("opaque ");
+            write_gate(source, name, params);
+            write_args(source, args);
+            source.push(';');
+        }
+        AstNode::Gate(name, regs, params, nodes) => {
+            source.push_str("gate ");
+            write_gate(source, name, params);
+            source.push_str(&regs.join(", "));
+            source.push_str(" {\n");
+            for node in nodes {
+                source.push_str("    ");
+                write_node(source, node);
+                source.push('\n');
+            }
+            source.push('}');
+        }
+        AstNode::If(alias, val, node) => {
+            source.push_str(&format!("if ({alias}=={val}) "));
+            write_node(source, node);
+        }
+    }
 }
 
 impl<'t> IntoIterator for Ast<'t> {
@@ -93,6 +184,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn to_source() {
+        for source in [
+            include_str!("../examples/test.qasm"),
+            include_str!("../examples/source/qec.qasm"),
+            include_str!("../examples/source/teleport.qasm"),
+            include_str!("../examples/source/ipea_3_pi_8.qasm"),
+            "OPENQASM 2.0; qreg q[1]; creg c[1]; opaque foo(x) q; barrier q; reset q[0];",
+        ] {
+            let ast = Ast::from_source(source).unwrap();
+            let generated = ast.to_source();
+            let regenerated = Ast::from_source(&generated).unwrap();
+
+            assert_eq!(ast.ast, regenerated.ast);
+            assert_eq!(generated, regenerated.to_source());
+        }
+    }
+
     #[test]
     fn empty_source() {
         assert_eq!(Ast::from_source(""), Err(Error::EmptySource));
@@ -1,6 +1,9 @@
 use std::{collections::VecDeque, fmt};
 
-use crate::{math::types::*, operator::MultiOp};
+use crate::{
+    math::types::*,
+    operator::{Applicable, MultiOp},
+};
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub enum Sep {
@@ -66,6 +69,79 @@ impl Op {
     }
 }
 
+impl Sep {
+    fn to_json(&self) -> String {
+        match *self {
+            Sep::Nop => r#"{"type":"nop"}"#.to_string(),
+            Sep::Measure(q, c) => format!(r#"{{"type":"measure","q":{},"c":{}}}"#, q, c),
+            Sep::IfBranch(c, v) => format!(r#"{{"type":"if","c":{},"value":{}}}"#, c, v),
+            Sep::Reset(q) => format!(r#"{{"type":"reset","q":{}}}"#, q),
+        }
+    }
+}
+
+impl Op {
+    pub(crate) fn to_json(&self) -> String {
+        fn gates_json(op: &MultiOp) -> String {
+            let gates = op
+                .iter()
+                .map(|gate| {
+                    format!(
+                        r#"{{"kind":"{}","act":{},"ctrl":{}}}"#,
+                        gate.kind(),
+                        gate.act_on() & !gate.ctrl(),
+                        gate.ctrl()
+                    )
+                })
+                .collect::<Vec<_>>();
+            format!("[{}]", gates.join(","))
+        }
+
+        let blocks = self
+            .0
+            .iter()
+            .map(|(op, sep)| format!(r#"{{"gates":{},"sep":{}}}"#, gates_json(op), sep.to_json()))
+            .collect::<Vec<_>>();
+        format!(
+            r#"{{"blocks":[{}],"last":{}}}"#,
+            blocks.join(","),
+            gates_json(&self.1)
+        )
+    }
+}
+
+impl Op {
+    /// Names of gates in program order, which do not affect any measurement or the final state,
+    /// since all their qubits are reset before being read.
+    pub(crate) fn dead_gates(&self) -> Vec<String> {
+        fn visit(op: &MultiOp, live: &mut N, dead: &mut Vec<String>) {
+            for gate in op.iter().rev().filter(|gate| !gate.is_identity()) {
+                if gate.act_on() & *live == 0 {
+                    dead.push(gate.name());
+                } else {
+                    *live |= gate.act_on();
+                }
+            }
+        }
+
+        let mut live = !0;
+        let mut dead = vec![];
+
+        visit(&self.1, &mut live, &mut dead);
+        for (op, sep) in self.0.iter().rev() {
+            match *sep {
+                Sep::Measure(q, _) => live |= q,
+                Sep::Reset(q) => live &= !q,
+                Sep::Nop | Sep::IfBranch(..) => {}
+            }
+            visit(op, &mut live, &mut dead);
+        }
+
+        dead.reverse();
+        dead
+    }
+}
+
 impl std::ops::Mul for Op {
     type Output = Self;
 
@@ -181,4 +257,35 @@ mod tests {
         let op = dummy_op();
         assert!(op.ends_with(&op));
     }
+
+    #[test]
+    fn json() {
+        let op = Op(
+            vec![(op::x(0b01).c(0b10).unwrap(), Sep::Measure(0b01, 0b10))].into(),
+            op::rz(0.5, 0b10),
+        );
+
+        assert_eq!(
+            op.to_json(),
+            r#"{"blocks":[{"gates":[{"kind":"CX","act":1,"ctrl":2}],"sep":{"type":"measure","q":1,"c":2}}],"last":[{"kind":"RZ","act":2,"ctrl":0}]}"#
+        );
+        assert_eq!(Op::default().to_json(), r#"{"blocks":[],"last":[]}"#);
+    }
+
+    #[test]
+    fn dead_gates() {
+        let op = Op(
+            vec![
+                (op::h(0b001) * op::x(0b010), Sep::Reset(0b011)),
+                (op::h(0b010) * op::cnot(0b010, 0b100), Sep::Reset(0b010)),
+                (op::y(0b001), Sep::Measure(0b001, 0b001)),
+                (op::z(0b001), Sep::IfBranch(0b001, 1)),
+            ]
+            .into(),
+            op::x(0b001),
+        );
+
+        assert_eq!(op.dead_gates(), ["H1", "X2"]);
+        assert!(dummy_op().dead_gates().is_empty());
+    }
 }
@@ -18,7 +18,7 @@ macro_rules! gate {
         } else if $args.len() != 0 {
             Err(Error::WrongArgNumber($name, $args.len()))
         } else {
-            Ok(op::$op(regs))
+            Ok(op::$op(regs).dgr())
         }
     }};
     ($name:expr, 2, $op:ident, $regs:expr, $args:expr) => {{
@@ -73,6 +73,33 @@ macro_rules! gate {
     }};
 }
 
+/// Names of built-in gates, accepted by [`process`] (in lower case).
+pub(crate) const NAMES: &[&str] = &[
+    "x",
+    "y",
+    "z",
+    "s",
+    "sdg",
+    "t",
+    "tdg",
+    "h",
+    "qft",
+    "rx",
+    "ry",
+    "rz",
+    "rxx",
+    "ryy",
+    "rzz",
+    "mcrz",
+    "swap",
+    "sqrt_swap",
+    "i_swap",
+    "sqrt_i_swap",
+    "u1",
+    "u2",
+    "u3",
+];
+
 pub(crate) fn process<'t>(name: &'t str, regs: Vec<N>, args: Vec<R>) -> Result<'t, MultiOp> {
     match name {
         s if matches!(&s[..1], "c" | "C") => {
@@ -110,6 +137,21 @@ pub(crate) fn process<'t>(name: &'t str, regs: Vec<N>, args: Vec<R>) -> Result<'
         "ryy" | "RYY" => gate!(name, r(2), ryy, regs, args),
         "rzz" | "RZZ" => gate!(name, r(2), rzz, regs, args),
 
+        "mcrz" | "MCRZ" => {
+            let (&target, ctrl) = regs.split_last().ok_or(Error::WrongRegNumber(name, 0))?;
+            let ctrl = ctrl.iter().fold(0, |acc, reg| acc | reg);
+            if ctrl == 0 || crate::math::count_bits(target) != 1 {
+                Err(Error::WrongRegNumber(
+                    name,
+                    crate::math::count_bits(ctrl) + crate::math::count_bits(target),
+                ))
+            } else if args.len() != 1 {
+                Err(Error::WrongArgNumber(name, args.len()))
+            } else {
+                op::mcrz(args[0], ctrl, target).ok_or(Error::InvalidControlMask(ctrl, target))
+            }
+        }
+
         "swap" | "SWAP" => gate!(name, 2, swap, regs, args),
         "sqrt_swap" | "SQRT_SWAP" => gate!(name, 2, sqrt_swap, regs, args),
         "i_swap" | "I_SWAP" => gate!(name, 2, i_swap, regs, args),
@@ -117,7 +159,7 @@ pub(crate) fn process<'t>(name: &'t str, regs: Vec<N>, args: Vec<R>) -> Result<'
 
         "u1" | "U1" => gate!(name, u1, regs, args),
         "u2" | "U2" => gate!(name, u2, regs, args),
-        "u3" | "U3" => gate!(name, u3, regs, args),
+        "u3" | "U3" | "U" => gate!(name, u3, regs, args),
 
         _ => Err(Error::UnknownGate(name)),
     }
@@ -126,6 +168,18 @@ pub(crate) fn process<'t>(name: &'t str, regs: Vec<N>, args: Vec<R>) -> Result<'
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::math::consts::TOLERANCE;
+
+    #[test]
+    fn names() {
+        for &name in NAMES {
+            assert_ne!(process(name, vec![], vec![]), Err(Error::UnknownGate(name)));
+            assert_ne!(
+                process(&format!("c{}", name), vec![], vec![]),
+                Err(Error::UnknownGate(&format!("c{}", name)))
+            );
+        }
+    }
 
     #[test]
     fn try_process_x() {
@@ -136,6 +190,13 @@ mod tests {
         );
     }
 
+    #[test]
+    fn try_process_dgr() {
+        assert_eq!(process("sdg", vec![0b011], vec![]), Ok(op::s(0b011).dgr()));
+        assert_eq!(process("tdg", vec![0b100], vec![]), Ok(op::t(0b100).dgr()));
+        assert_ne!(process("sdg", vec![0b011], vec![]), Ok(op::s(0b011)));
+    }
+
     #[test]
     fn try_process_cx() {
         assert_eq!(
@@ -229,6 +290,115 @@ mod tests {
         );
     }
 
+    #[test]
+    fn try_process_controlled_unitary() {
+        assert_eq!(
+            process("cu1", vec![0b010, 0b001], vec![1.0]),
+            Ok(op::u1(1.0, 0b001).c(0b010).unwrap()),
+        );
+        assert_eq!(
+            process("cu3", vec![0b010, 0b001], vec![1.0, 2.0, 3.0]),
+            op::cu3(1.0, 2.0, 3.0, 0b010, 0b001).ok_or(Error::UnknownGate("cu3")),
+        );
+        assert_eq!(
+            process("cu3", vec![0b001], vec![1.0, 2.0, 3.0]),
+            Err(Error::WrongRegNumber("cu3", 1)),
+        );
+        assert_eq!(
+            process("cu3", vec![0b010, 0b001], vec![1.0, 2.0]),
+            Err(Error::WrongArgNumber("cu3", 2)),
+        );
+    }
+
+    #[test]
+    fn try_process_primitives() {
+        use crate::math::consts::PI;
+
+        let u = process("U", vec![0b001], vec![PI, 0.0, PI]).unwrap();
+        assert_eq!(u, op::u3(PI, 0.0, PI, 0b001));
+        assert!(u.is_equivalent(&op::x(0b001), 1, 10. * TOLERANCE));
+        assert_eq!(
+            process("U", vec![0b001], vec![PI]),
+            Err(Error::WrongArgNumber("U", 1)),
+        );
+
+        assert_eq!(
+            process("CX", vec![0b010, 0b001], vec![]),
+            process("cx", vec![0b010, 0b001], vec![]),
+        );
+        assert_eq!(
+            process("CX", vec![0b010, 0b001], vec![]),
+            Ok(op::cnot(0b010, 0b001)),
+        );
+    }
+
+    #[test]
+    fn try_process_controlled_rotation() {
+        assert_eq!(
+            process("crx", vec![0b100, 0b001], vec![1.0]),
+            op::crx(1.0, 0b100, 0b001).ok_or(Error::UnknownGate("crx")),
+        );
+        assert_eq!(
+            process("cry", vec![0b001, 0b010], vec![2.0]),
+            op::cry(2.0, 0b001, 0b010).ok_or(Error::UnknownGate("cry")),
+        );
+        assert_eq!(
+            process("crz", vec![0b010, 0b100], vec![3.0]),
+            op::crz(3.0, 0b010, 0b100).ok_or(Error::UnknownGate("crz")),
+        );
+        assert_eq!(
+            process("ccrz", vec![0b001, 0b010, 0b100], vec![3.0]),
+            op::crz(3.0, 0b011, 0b100).ok_or(Error::UnknownGate("ccrz")),
+        );
+
+        for name in ["crx", "cry", "crz"] {
+            assert_eq!(
+                process(name, vec![0b100], vec![1.0]),
+                Err(Error::WrongRegNumber(name, 1)),
+            );
+            assert_eq!(
+                process(name, vec![0b100, 0b011], vec![1.0]),
+                Err(Error::WrongRegNumber(name, 3)),
+            );
+            assert_eq!(
+                process(name, vec![0b100, 0b001], vec![]),
+                Err(Error::WrongArgNumber(name, 0)),
+            );
+            assert_eq!(
+                process(name, vec![0b100, 0b100], vec![1.0]),
+                Err(Error::InvalidControlMask(0b100, 0b100)),
+            );
+        }
+    }
+
+    #[test]
+    fn try_process_mcrz() {
+        assert_eq!(
+            process("mcrz", vec![0b0001, 0b0100, 0b0010], vec![1.0]),
+            op::mcrz(1.0, 0b0101, 0b0010).ok_or(Error::UnknownGate("mcrz")),
+        );
+        assert_eq!(
+            process("MCRZ", vec![0b0110, 0b1000], vec![1.0]),
+            process("ccrz", vec![0b0010, 0b0100, 0b1000], vec![1.0]),
+        );
+        assert_eq!(
+            process("mcrz", vec![0b0001], vec![1.0]),
+            Err(Error::WrongRegNumber("mcrz", 1)),
+        );
+        assert_eq!(
+            process("mcrz", vec![0b0001, 0b0110], vec![1.0]),
+            Err(Error::WrongRegNumber("mcrz", 3)),
+        );
+        assert_eq!(
+            process("mcrz", vec![0b0001, 0b0010], vec![]),
+            Err(Error::WrongArgNumber("mcrz", 0)),
+        );
+        assert_eq!(
+            process("mcrz", vec![0b0011, 0b0010], vec![1.0]),
+            Err(Error::InvalidControlMask(0b0011, 0b0010)),
+        );
+    }
+
     #[test]
     fn try_process_any() {
         assert_eq!(process("x", vec![0b001, 0b100], vec![]), Ok(op::x(0b101)),);
@@ -108,6 +108,19 @@ impl<'t> Macro<'t> {
         regs: Vec<N>,
         args: Vec<R>,
         macros: &HashMap<&'t str, Macro<'t>>,
+    ) -> super::Result<'t, MultiOp> {
+        self.expand(name, regs, args, macros, &mut vec![name])
+    }
+
+    // `stack` holds names of macros, which are being expanded,
+    // so both direct and indirect recursion are detected
+    fn expand(
+        &self,
+        name: &'t str,
+        regs: Vec<N>,
+        args: Vec<R>,
+        macros: &HashMap<&'t str, Macro<'t>>,
+        stack: &mut Vec<&'t str>,
     ) -> super::Result<'t, MultiOp> {
         if regs.len() != self.regs.len() {
             return Err(super::Error::WrongRegNumber(name, regs.len()));
@@ -137,10 +150,13 @@ impl<'t> Macro<'t> {
 
                 let op_res = match macros.get(*name_i) {
                     Some(_macro) => {
-                        if &name == name_i {
+                        if stack.contains(name_i) {
                             return Err(Error::RecursiveMacro(name_i).into());
                         }
-                        _macro.process(name_i, regs_i, args_i, macros)?
+                        stack.push(name_i);
+                        let op_res = _macro.expand(name_i, regs_i, args_i, macros, stack)?;
+                        stack.pop();
+                        op_res
                     }
                     None => gates::process(name_i, regs_i, args_i)?,
                 };
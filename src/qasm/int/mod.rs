@@ -1,7 +1,7 @@
 #![allow(clippy::boxed_local)]
 #![allow(clippy::needless_lifetimes)]
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use qasm::{Argument, AstNode};
 
@@ -105,6 +105,56 @@ impl<'t> Int<'t> {
         int.append_int(self)
     }
 
+    /// Return names of built-in gates and user-defined macros.
+    /// Built-in gates come first, and macros are sorted by name.
+    ///
+    /// Note, that every gate could be used with ```c``` prefix to add control qubit, e.g. ```cx``` or ```ccx```.
+    pub fn gate_names(&self) -> Vec<&str> {
+        let mut macros = self.macros.keys().copied().collect::<Vec<_>>();
+        macros.sort_unstable();
+        gates::NAMES.iter().copied().chain(macros).collect()
+    }
+
+    /// Return number of gates of each type in the program.
+    ///
+    /// Gates are counted by their names without masks and parameters, with one ```C``` per control qubit,
+    /// e.g. both ```cx``` and ```CX``` are counted as ```"CX"```, and ```ccx``` as ```"CCX"```.
+    /// Measured and reset qubits are counted as ```"measure"``` and ```"reset"``` respectively.
+    /// Gates of conditional branches are counted regardless of condition.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let ast = Ast::from_source("OPENQASM 2.0; qreg q[2]; creg c[2]; h q[0]; cx q[0], q[1]; measure q -> c;").unwrap();
+    /// let stats = Int::new(ast).unwrap().statistics();
+    ///
+    /// assert_eq!(stats["H"], 1);
+    /// assert_eq!(stats["CX"], 1);
+    /// assert_eq!(stats["measure"], 2);
+    /// ```
+    pub fn statistics(&self) -> BTreeMap<String, usize> {
+        let mut stats = BTreeMap::new();
+
+        let ExtOp(ops, last) = &self.q_ops;
+        for op in ops.iter().map(|(op, _)| op).chain([last]) {
+            for gate in op.iter() {
+                *stats.entry(gate.kind()).or_default() += 1;
+            }
+        }
+        for (_, sep) in ops {
+            match *sep {
+                Sep::Measure(q_arg, _) => {
+                    *stats.entry("measure".to_string()).or_default() += q_arg.count_ones() as usize
+                }
+                Sep::Reset(q_arg) => {
+                    *stats.entry("reset".to_string()).or_default() += q_arg.count_ones() as usize
+                }
+                Sep::Nop | Sep::IfBranch(..) => {}
+            }
+        }
+
+        stats
+    }
+
     pub fn xor(self) -> Self {
         Self {
             m_op: MeasureOp::Xor,
@@ -127,7 +177,7 @@ impl<'t> Int<'t> {
         match node {
             AstNode::QReg(alias, size) => self.process_qreg(changes, alias, size as N),
             AstNode::CReg(alias, size) => self.process_creg(changes, alias, size as N),
-            AstNode::Barrier(_) => self.process_barrier(changes),
+            AstNode::Barrier(reg) => self.process_barrier(changes, reg),
             AstNode::Reset(reg) => self.process_reset(changes, reg),
             AstNode::Measure(q_arg, c_arg) => self.process_measure(changes, q_arg, c_arg),
             AstNode::ApplyGate(name, regs, args) => {
@@ -186,6 +236,10 @@ impl<'t> Int<'t> {
     fn process_qreg(&self, changes: &mut Self, alias: &'t str, q_num: N) -> Result<'t, ()> {
         Self::check_ident(alias)?;
         Self::check_reg_size(alias, q_num)?;
+        let total = self.q_reg.len() + changes.q_reg.len() + q_num;
+        if total > crate::register::max_qubits() {
+            return Err(Error::RegisterIsTooLarge(alias, total));
+        }
         self.check_dup(changes, alias)?;
         changes.q_reg.append(&mut vec![alias; q_num]);
         Ok(())
@@ -199,8 +253,9 @@ impl<'t> Int<'t> {
         Ok(())
     }
 
-    fn process_barrier(&self, _changes: &mut Self) -> Result<'t, ()> {
-        //  Does not really affect qvnt-i flow
+    fn process_barrier(&self, changes: &mut Self, q_reg: Argument<'t>) -> Result<'t, ()> {
+        let idx = self.get_q_idx_with_context(changes, q_reg)?;
+        changes.q_ops.push(op::barrier(idx));
         Ok(())
     }
 
@@ -399,6 +454,44 @@ impl<'t> Int<'t> {
         format!("{:?}", self.q_ops)
     }
 
+    /// Return operation tree as JSON, suitable for external tools.
+    ///
+    /// Tree is an object with ```"blocks"``` and ```"last"``` fields.
+    /// Every block contains ```"gates"```, which are applied in order,
+    /// followed by separator ```"sep"``` of one of types ```"nop"```, ```"measure"```, ```"if"``` or ```"reset"```.
+    /// ```"last"``` contains gates after the last separator.
+    /// Every gate is described by its ```"kind"``` (see [`statistics`](Self::statistics)),
+    /// ```"act"``` and ```"ctrl"``` masks of target and control qubits.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let ast = Ast::from_source("OPENQASM 2.0; qreg q[2]; creg c[2]; h q[0]; measure q[0] -> c[0]; cx q[0], q[1];").unwrap();
+    /// let json = Int::new(ast).unwrap().ops_tree_json();
+    ///
+    /// assert!(json.contains(r#""type":"measure""#));
+    /// assert!(json.contains(r#"{"kind":"CX","act":2,"ctrl":1}"#));
+    /// ```
+    pub fn ops_tree_json(&self) -> String {
+        self.q_ops.to_json()
+    }
+
+    /// Return names of gates, whose effect is overwritten before it could be observed.
+    ///
+    /// Gate is dead, if every qubit it acts on is reset before it is measured or used by another alive gate.
+    /// Gates after the last reset are always alive, since they form the final state.
+    /// Such gates could be safely removed from the circuit.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let ast = Ast::from_source("OPENQASM 2.0; qreg q[2]; creg c[2]; h q[0]; x q[1]; reset q[0]; measure q -> c;").unwrap();
+    /// let dead = Int::new(ast).unwrap().dead_gates();
+    ///
+    /// assert_eq!(dead, ["H1"]);
+    /// ```
+    pub fn dead_gates(&self) -> Vec<String> {
+        self.q_ops.dead_gates()
+    }
+
     pub fn get_q_alias(&self) -> String {
         format!("{:?}", self.q_reg)
     }
@@ -411,6 +504,7 @@ impl<'t> Int<'t> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::math::consts::TOLERANCE;
 
     #[test]
     fn regs() {
@@ -472,6 +566,55 @@ mod tests {
         assert_ne!(int.asts.len(), 0);
     }
 
+    #[test]
+    fn primitive_gates() {
+        let int = int_from_source("qreg q[2]; U(pi, 0, pi) q[0]; CX q[0], q[1];").unwrap();
+        let mut sym = crate::qasm::Sym::new(int);
+        sym.finish();
+
+        let probs = sym.get_probabilities();
+        assert!((probs[0b11] - 1.0).abs() < 10. * TOLERANCE);
+    }
+
+    #[test]
+    fn statistics() {
+        let ast = Ast::from_source(include_str!("../examples/source/adder.qasm")).unwrap();
+        let stats = Int::new(ast).unwrap().statistics();
+
+        // 4 majority and 4 unmaj macros contain 2 CNOT and 1 Toffoli gates each
+        assert_eq!(stats["X"], 2);
+        assert_eq!(stats["CX"], 17);
+        assert_eq!(stats["CCX"], 8);
+        assert_eq!(stats["measure"], 5);
+        assert!(!stats.contains_key("reset"));
+
+        let stats = int_from_source("qreg q[2]; reset q; rz(0.5) q[1];")
+            .unwrap()
+            .statistics();
+        assert_eq!(
+            stats.into_iter().collect::<Vec<_>>(),
+            [("RZ".to_string(), 1), ("reset".to_string(), 2)]
+        );
+    }
+
+    #[test]
+    fn gate_names() {
+        let int = int_from_source("qreg q[2];").unwrap();
+        assert!(int.gate_names().contains(&"h"));
+        assert!(!int.gate_names().contains(&"foo"));
+
+        let int = int_from_source(
+            "qreg q[2];\
+            gate foo a, b { h a; cx a, b; }\
+            gate bar(x) a { rx(x) a; }",
+        )
+        .unwrap();
+        let names = int.gate_names();
+        assert!(names.contains(&"h"));
+        assert!(names.contains(&"u3"));
+        assert_eq!(names[names.len() - 2..], ["bar", "foo"]);
+    }
+
     fn int_from_source(source: &'static str) -> Result<Int> {
         let ast = Ast::from_source(source).unwrap();
         Int::new(ast)
@@ -539,6 +682,10 @@ mod tests {
             int_from_source("qreg q[4]; cx q[0], q;"),
             Err(Error::InvalidControlMask(0b0001, 0b1111)),
         );
+        assert_eq!(
+            int_from_source("qreg q[2]; ccx q[0], q[0], q[1];"),
+            Err(Error::InvalidControlMask(0b01, 0b11)),
+        );
     }
 
     #[test]
@@ -592,6 +739,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn gate_args_with_functions() {
+        use crate::math::from_f64;
+        use std::f64::consts::PI;
+
+        let int = int_from_source(
+            "qreg q[1];\
+            rx(exp(1)) q[0];\
+            ry(ln(pi)) q[0];\
+            rz(sqrt(2) * sin(pi/6) + cos(pi/3) * tan(pi/4)) q[0];",
+        )
+        .unwrap();
+
+        // arguments are evaluated in double precision
+        let phase = 2f64.sqrt() * (PI / 6.).sin() + (PI / 3.).cos() * (PI / 4.).tan();
+        assert_eq!(
+            int.q_ops.1,
+            op::rx(from_f64(1f64.exp()), 0b1)
+                * op::ry(from_f64(PI.ln()), 0b1)
+                * op::rz(from_f64(phase), 0b1)
+        );
+    }
+
+    #[test]
+    fn barrier() {
+        let int = int_from_source("qreg q[3]; h q[0]; barrier q; h q[0];").unwrap();
+        assert_eq!(format!("{:?}", int.q_ops.1), "[H1, Barrier7, H1]");
+
+        let int = int_from_source("qreg q[3]; barrier q[1];").unwrap();
+        assert_eq!(int.q_ops.1.act_on(), 0b010);
+
+        assert_eq!(
+            int_from_source("qreg q[3]; barrier r;"),
+            Err(Error::NoQReg("r"))
+        );
+    }
+
     #[test]
     fn invalid_ident() {
         assert_eq!(
@@ -611,6 +795,53 @@ mod tests {
             int_from_source("qreg q[64];"),
             Err(Error::RegisterIsTooLarge("q", 64))
         );
+        assert_eq!(
+            int_from_source("qreg q[20]; qreg r[11];"),
+            Err(Error::RegisterIsTooLarge("r", 31))
+        );
+        assert!(int_from_source("qreg q[20]; qreg r[10]; creg c[40];").is_ok());
+    }
+
+    #[test]
+    fn nested_macro() {
+        let int = int_from_source(
+            "gate bell a, b { h a; cx a, b; }\
+            gate foo q0, q1, q2 { bell q0, q1; bell q1, q2; }\
+            qreg q[3];\
+            foo q[0], q[1], q[2];",
+        )
+        .unwrap();
+        assert_eq!(
+            int.q_ops.1,
+            op::h(0b001) * op::cx(0b001, 0b010) * op::h(0b010) * op::cx(0b010, 0b100)
+        );
+
+        // macro, which is defined later, is visible from the earlier one
+        let int = int_from_source(
+            "gate foo q0, q1 { bar q1, q0; }\
+            gate bar a, b { cx a, b; }\
+            qreg q[2];\
+            foo q[0], q[1];",
+        )
+        .unwrap();
+        assert_eq!(int.q_ops.1, op::cx(0b10, 0b01));
+    }
+
+    #[test]
+    fn recursive_macro() {
+        assert_eq!(
+            int_from_source("gate a q { a q; } qreg q[1]; a q[0];"),
+            Err(Error::MacroError(macros::Error::RecursiveMacro("a")))
+        );
+        assert_eq!(
+            int_from_source(
+                "gate a q { b q; } gate b q { c q; } gate c q { a q; } qreg q[1]; a q[0];"
+            ),
+            Err(Error::MacroError(macros::Error::RecursiveMacro("a")))
+        );
+        assert!(
+            int_from_source("gate a q { h q; } gate b q { a q; a q; } qreg q[1]; b q[0];").is_ok()
+        );
     }
 
     #[test]
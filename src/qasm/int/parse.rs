@@ -1,17 +1,22 @@
 use meval::*;
 
-use crate::math::{consts::*, types::*};
+use crate::math::types::*;
 
+// Expressions are always evaluated in double precision, even with `f32` feature.
 thread_local! {
     static EXAUSTIVE_CONTEXT: Context<'static> = {
         let mut ctx = Context::empty();
-        ctx.var("pi", PI);
+        ctx.var("pi", std::f64::consts::PI);
 
         ctx.func("sqrt", f64::sqrt);
         ctx.func("exp", f64::exp);
         ctx.func("ln", f64::ln);
         ctx.func("abs", f64::abs);
 
+        ctx.func("sin", f64::sin);
+        ctx.func("cos", f64::cos);
+        ctx.func("tan", f64::tan);
+
         ctx.func("floor", f64::floor);
         ctx.func("ceil", f64::ceil);
         ctx.func("round", f64::round);
@@ -27,27 +32,32 @@ thread_local! {
 pub use meval::Error;
 pub type Result<T> = std::result::Result<T, meval::Error>;
 
-pub(crate) fn eval_extended<'t, V: IntoIterator<Item = (&'t str, f64)>>(
+pub(crate) fn eval_extended<'t, V: IntoIterator<Item = (&'t str, R)>>(
     expr: &'t str,
     vars: V,
 ) -> Result<R> {
     let mut ctx = EXAUSTIVE_CONTEXT.with(|ctx| ctx.clone());
     for (var, value) in vars {
-        ctx.var(var, value);
+        ctx.var(var, crate::math::to_f64(value));
     }
 
-    expr.parse::<Expr>()?.eval_with_context(ctx)
+    expr.parse::<Expr>()?
+        .eval_with_context(ctx)
+        .map(crate::math::from_f64)
 }
 
 #[cfg(test)]
 mod tests {
+    use std::f64::consts::*;
+
     use super::*;
+    use crate::math::from_f64;
 
     #[test]
     fn parse_expr() {
         let expr = "2 * pi / 16";
 
-        assert_eq!(eval_extended(expr, vec![]), Ok(2. * PI / 16.));
+        assert_eq!(eval_extended(expr, vec![]), Ok(from_f64(2. * PI / 16.)));
     }
 
     #[test]
@@ -58,6 +68,28 @@ mod tests {
             eval_extended(expr, vec![]),
             Err(Error::UnknownVariable("x".to_string()))
         );
-        assert_eq!(eval_extended(expr, vec![("x", PI)]), Ok(2. * PI / 16.));
+        assert_eq!(
+            eval_extended(expr, vec![("x", from_f64(PI))]),
+            Ok(from_f64(2. * PI / 16.))
+        );
+    }
+
+    #[test]
+    fn parse_expr_with_functions() {
+        assert_eq!(eval_extended("sqrt(2)", vec![]), Ok(from_f64(2f64.sqrt())));
+        assert_eq!(eval_extended("exp(1)", vec![]), Ok(from_f64(1f64.exp())));
+        assert_eq!(eval_extended("ln(pi)", vec![]), Ok(from_f64(PI.ln())));
+        assert_eq!(
+            eval_extended("sin(pi/6)", vec![]),
+            Ok(from_f64((PI / 6.).sin()))
+        );
+        assert_eq!(
+            eval_extended("cos(pi/3)", vec![]),
+            Ok(from_f64((PI / 3.).cos()))
+        );
+        assert_eq!(
+            eval_extended("tan(pi/4)", vec![]),
+            Ok(from_f64((PI / 4.).tan()))
+        );
     }
 }
@@ -11,6 +11,10 @@ mod tests {
     use test_case::test_case;
 
     use super::*;
+    use crate::{
+        math::consts::{FRAC_PI_2, FRAC_PI_4, TOLERANCE},
+        register::CReg,
+    };
 
     #[test_case(include_str!("./examples/source/adder.qasm"); "adder")]
     #[test_case(include_str!("./examples/source/bigadder.qasm"); "big_adder")]
@@ -37,4 +41,224 @@ mod tests {
         sym.reset();
         sym.finish();
     }
+
+    #[test]
+    fn to_qasm_round_trip() {
+        use crate::prelude::*;
+
+        let circuit = op::h(0b0011)
+            * op::cnot(0b0001, 0b1100)
+            * op::rx(0.3, 0b0100).c(0b1000).unwrap()
+            * op::ry(0.7, 0b0010).c(0b1000).unwrap()
+            * op::rxx(-1.2, 0b1001)
+            * op::rzz(0.4, 0b0110)
+            * op::s(0b0110).dgr()
+            * op::t(0b1000).c(0b0001).unwrap()
+            * op::s(0b0100).dgr().c(0b0010).unwrap()
+            * op::rz(2.5, 0b0001)
+            * op::rz(-0.5, 0b0010).c(0b0001).unwrap()
+            * op::h(0b1100).c(0b0001).unwrap()
+            * op::barrier(0b0101)
+            * op::swap(0b0110).c(0b1000).unwrap()
+            * op::y(0b1001)
+            * op::z(0b0011).c(0b0100).unwrap()
+            * op::x(0b0001).c(0b0110).unwrap();
+
+        let source = circuit.to_qasm(4).unwrap();
+        assert!(source.starts_with("OPENQASM 2.0;\ninclude \"qelib1.inc\";\n"));
+        assert_eq!(
+            (op::h(0b011).c(0b100).unwrap() * op::s(0b110).c(0b001).unwrap()).to_qasm(3),
+            Some(format!(
+                "OPENQASM 2.0;\ninclude \"qelib1.inc\";\nqreg q[3];\n\
+                ch q[2], q[0];\nch q[2], q[1];\n\
+                crz({0}) q[0], q[1];\nu1({1}) q[0];\n\
+                crz({0}) q[0], q[2];\nu1({1}) q[0];\n",
+                FRAC_PI_2, FRAC_PI_4
+            ))
+        );
+
+        let ast = Ast::from_source(&source).unwrap();
+        let int = Int::new(ast).unwrap();
+        assert!(int.q_ops.0.is_empty());
+        // gates are the same up to global phase, e.g. u1 is read as rz
+        assert!(int.q_ops.1.is_equivalent(&circuit, 4, 10. * TOLERANCE));
+
+        assert_eq!(circuit.to_qasm(3), None);
+        assert!(circuit.to_qasm(usize::BITS as usize).is_some());
+        assert_eq!(op::sqrt_swap(0b11).dgr().to_qasm(2), None);
+        assert_eq!(op::i_swap(0b11).to_qasm(2), None);
+        assert_eq!(op::ryy(1.0, 0b11).to_qasm(2), None);
+        assert_eq!(op::x(0b1000).c(0b0111).unwrap().to_qasm(4), None);
+        assert_eq!(op::rx(1.0, 0b100).c(0b011).unwrap().to_qasm(3), None);
+    }
+
+    #[test]
+    fn finish_with_progress() {
+        let ast = Ast::from_source(
+            "OPENQASM 2.0;\
+            qreg q[3];\
+            creg c[3];\
+            x q[0];\
+            h q[1], q[2];\
+            measure q[0] -> c[0];\
+            if (c==0) z q[1];\
+            if (c==1) z q[2];\
+            h q[1], q[2];",
+        )
+        .unwrap();
+        let int = Int::new(ast).unwrap();
+        let mut sym = Sym::new(int);
+
+        let mut progress = vec![];
+        sym.finish_with_progress(|done, total| progress.push((done, total)));
+
+        let total = progress[0].1;
+        assert!(total >= 5);
+        assert_eq!(
+            progress,
+            (1..=total).map(|done| (done, total)).collect::<Vec<_>>()
+        );
+        assert_eq!(sym.get_class().get(), 0b001);
+
+        let probs = sym.get_probabilities();
+        assert!((probs[0b101] - 1.0).abs() < 10. * TOLERANCE);
+    }
+
+    #[test]
+    fn measurement_history() {
+        let ast = Ast::from_source(
+            "OPENQASM 2.0;\
+            qreg q[2];\
+            creg c[2];\
+            x q[0];\
+            measure q -> c;\
+            if (c==1) x q[1];\
+            measure q -> c;",
+        )
+        .unwrap();
+        let int = Int::new(ast).unwrap();
+        let mut sym = Sym::new(int);
+
+        sym.finish();
+        let history: Vec<_> = sym
+            .measurement_history()
+            .into_iter()
+            .map(|(_, c)| c.get())
+            .collect();
+        assert_eq!(history, [0b01, 0b01, 0b11]);
+        assert_eq!(sym.get_class().get(), 0b11);
+
+        sym.reset();
+        assert!(sym.measurement_history().is_empty());
+    }
+
+    #[test]
+    fn measure_single_bit() {
+        let source = "OPENQASM 2.0;\
+            qreg q[3];\
+            creg c[4];\
+            x q[0], q[2];\
+            measure q[0] -> c[2];\
+            measure q[1] -> c[0];\
+            measure q[2] -> c[3];";
+        let mut sym = Sym::new(Int::new(Ast::from_source(source).unwrap()).unwrap());
+        sym.finish();
+        assert_eq!(sym.get_class().get(), 0b1100);
+
+        let source = "OPENQASM 2.0;\
+            qreg a[2];\
+            qreg b[2];\
+            creg c[2];\
+            creg d[2];\
+            x b[1];\
+            measure b -> d;\
+            measure b[1] -> c[0];";
+        let mut sym = Sym::new(Int::new(Ast::from_source(source).unwrap()).unwrap());
+        sym.finish();
+        assert_eq!(sym.get_class().get(), 0b1001);
+    }
+
+    #[test]
+    fn reset_classical() {
+        let source = "OPENQASM 2.0;\
+            qreg q[2];\
+            creg c[2];\
+            x q[0];\
+            measure q -> c;\
+            if (c==1) x q[1];";
+        let mut sym = Sym::new(Int::new(Ast::from_source(source).unwrap()).unwrap());
+
+        sym.finish();
+        assert_eq!(sym.get_class().get(), 0b01);
+        assert_eq!(sym.get_probabilities()[0b11], 1.0);
+
+        sym.reset_classical();
+        assert_eq!(sym.get_class().get(), 0);
+        assert!(sym.measurement_history().is_empty());
+        assert_eq!(sym.get_probabilities()[0b11], 1.0);
+
+        // re-running keeps evolving the same quantum state
+        sym.finish();
+        assert_eq!(sym.get_class().get(), 0b10);
+        assert_eq!(sym.get_probabilities()[0b10], 1.0);
+    }
+
+    #[test]
+    fn step() {
+        let source = "OPENQASM 2.0;\
+            qreg q[2];\
+            creg c[2];\
+            x q[0];\
+            measure q[0] -> c[0];\
+            x q[1];\
+            measure q[1] -> c[1];\
+            x q[0];";
+        let int = Int::new(Ast::from_source(source).unwrap()).unwrap();
+        let mut sym = Sym::new(int);
+
+        assert_eq!(sym.step().map(CReg::get), Some(0b01));
+        assert_eq!(sym.get_probabilities()[0b01], 1.0);
+
+        assert_eq!(sym.step().map(CReg::get), Some(0b11));
+        assert_eq!(sym.get_probabilities()[0b11], 1.0);
+
+        assert_eq!(sym.step().map(CReg::get), None);
+        assert_eq!(sym.get_probabilities()[0b10], 1.0);
+        assert_eq!(sym.measurement_history().len(), 2);
+
+        // stepping is resumable by finish
+        sym.reset();
+        assert_eq!(sym.step().map(CReg::get), Some(0b01));
+        sym.finish();
+        assert_eq!(sym.get_class().get(), 0b11);
+        assert_eq!(sym.get_probabilities()[0b10], 1.0);
+    }
+
+    #[test]
+    fn run() {
+        let source = "OPENQASM 2.0;\
+            qreg q[3];\
+            creg c[1];\
+            h q[0];\
+            x q[1];\
+            measure q[1] -> c[0];\
+            if (c==1) cx q[1], q[2];";
+        let int = Int::new(Ast::from_source(source).unwrap()).unwrap();
+        let mut sym = Sym::new(int.clone());
+
+        // state left by previous run is discarded
+        sym.finish();
+        let (psi, c_reg) = sym.run(int.clone());
+
+        let mut manual = Sym::new(int);
+        manual.reset();
+        manual.finish();
+        assert_eq!(c_reg, manual.get_class());
+        assert_eq!(c_reg.get(), 1);
+
+        let polar: Vec<_> = psi.iter().map(|z| z.to_polar()).collect();
+        assert_eq!(polar, manual.get_polar_wavefunction());
+        assert!((psi[0b110].re - crate::math::consts::FRAC_1_SQRT_2).abs() < TOLERANCE);
+        assert!((psi[0b111].re - crate::math::consts::FRAC_1_SQRT_2).abs() < TOLERANCE);
+    }
 }
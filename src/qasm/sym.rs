@@ -10,6 +10,8 @@ pub struct Sym {
     q_reg: QReg,
     c_reg: CReg,
     q_ops: ExtOp,
+    history: Vec<(N, CReg)>,
+    cursor: N,
 }
 
 impl Sym {
@@ -19,6 +21,8 @@ impl Sym {
             q_reg: QReg::new(int.q_reg.len()),
             c_reg: CReg::new(int.c_reg.len()),
             q_ops: int.q_ops,
+            history: vec![],
+            cursor: 0,
         }
     }
 
@@ -35,42 +39,143 @@ impl Sym {
     pub fn reset(&mut self) {
         self.q_reg.reset(0);
         self.c_reg.reset(0);
+        self.history.clear();
+        self.cursor = 0;
+    }
+
+    /// Reset only classical register and [`measurement history`](Sym::measurement_history),
+    /// keeping quantum state and the position of [`step()`](Sym::step).
+    ///
+    /// Subsequent measurements are recorded into cleared classical register,
+    /// while conditional branches see only results of those new measurements.
+    ///
+    /// ```rust
+    /// # use qvnt::{prelude::*, qasm::Sym};
+    /// let ast = Ast::from_source("OPENQASM 2.0; qreg q[1]; creg c[1]; x q[0]; measure q -> c;").unwrap();
+    /// let mut sym = Sym::new(Int::new(ast).unwrap());
+    /// sym.finish();
+    ///
+    /// sym.reset_classical();
+    /// assert_eq!(sym.get_class().get(), 0);
+    /// assert_eq!(sym.get_probabilities()[1], 1.0);
+    /// ```
+    pub fn reset_classical(&mut self) {
+        self.c_reg.reset(0);
+        self.history.clear();
     }
 
     pub fn finish(&mut self) -> &mut Self {
-        for (op, sep) in self.q_ops.0.iter() {
-            match *sep {
-                Sep::Nop => {
-                    self.q_reg.apply(op);
-                }
-                Sep::Measure(q_arg, c_arg) => {
-                    self.q_reg.apply(op);
-
-                    let mask = self.q_reg.measure_mask(q_arg);
-                    let mut c_reg = self.c_reg.clone();
-                    match self.m_op {
-                        MeasureOp::Set => BitsIter::from(q_arg)
-                            .zip(BitsIter::from(c_arg))
-                            .for_each(|(q, c)| c_reg.set(mask.get() & q != 0, c)),
-                        MeasureOp::Xor => BitsIter::from(q_arg)
-                            .zip(BitsIter::from(c_arg))
-                            .for_each(|(q, c)| c_reg.xor(mask.get() & q != 0, c)),
-                    };
-                    self.c_reg = c_reg;
-                }
-                Sep::IfBranch(c, v) => {
-                    if self.c_reg.get_by_mask(c) == v {
-                        self.q_reg.apply(op);
-                    }
-                }
-                Sep::Reset(q) => {
-                    self.q_reg.apply(op);
-                    self.q_reg.reset_by_mask(q);
+        self.finish_with_progress(|_, _| {})
+    }
+
+    /// Same as [`finish()`](Sym::finish), but *callback* is invoked after every gate
+    /// with the number of processed gates and the total number of gates.
+    /// Gates of a skipped conditional branch are counted as processed.
+    ///
+    /// ```rust
+    /// # use qvnt::{prelude::*, qasm::Sym};
+    /// let ast = Ast::from_source("OPENQASM 2.0; qreg q[2]; h q[0]; cx q[0], q[1];").unwrap();
+    /// let mut sym = Sym::new(Int::new(ast).unwrap());
+    ///
+    /// let mut progress = vec![];
+    /// sym.finish_with_progress(|done, total| progress.push((done, total)));
+    ///
+    /// assert_eq!(progress, [(1, 2), (2, 2)]);
+    /// ```
+    pub fn finish_with_progress<F>(&mut self, mut callback: F) -> &mut Self
+    where
+        F: FnMut(N, N),
+    {
+        let total = self.q_ops.0.iter().map(|(op, _)| op.len()).sum::<N>() + self.q_ops.1.len();
+        let mut done = self
+            .q_ops
+            .0
+            .iter()
+            .take(self.cursor)
+            .map(|(op, _)| op.len())
+            .sum::<N>();
+
+        let mut after = || {
+            done += 1;
+            callback(done, total);
+        };
+        while self.cursor < self.q_ops.0.len() {
+            self.cursor += 1;
+            self.run_segment(self.cursor - 1, &mut after);
+        }
+        self.run_tail(&mut after);
+        self
+    }
+
+    /// Execute gates up to and including the next measurement, conditional branch or reset.
+    ///
+    /// Returns classical register after the executed segment,
+    /// or [`None`], if the rest of circuit was executed and there are no more segments.
+    /// After [`None`] was returned, next call starts the circuit from the beginning on the current state,
+    /// as [`finish()`](Sym::finish) does.
+    /// Calling [`finish()`](Sym::finish) in the middle of stepping executes the remaining part of circuit.
+    ///
+    /// ```rust
+    /// # use qvnt::{prelude::*, qasm::Sym};
+    /// let ast = Ast::from_source("
+    ///     OPENQASM 2.0;
+    ///     qreg q[1]; creg c[2];
+    ///     x q[0];
+    ///     measure q[0] -> c[0];
+    ///     x q[0];
+    ///     measure q[0] -> c[1];
+    /// ").unwrap();
+    /// let mut sym = Sym::new(Int::new(ast).unwrap());
+    ///
+    /// let mut results = vec![];
+    /// while let Some(c_reg) = sym.step() {
+    ///     results.push(c_reg.get());
+    /// }
+    ///
+    /// assert_eq!(results, [0b01, 0b01]);
+    /// ```
+    pub fn step(&mut self) -> Option<&CReg> {
+        while self.cursor < self.q_ops.0.len() {
+            self.cursor += 1;
+            self.run_segment(self.cursor - 1, &mut || {});
+            if self.q_ops.0[self.cursor - 1].1 != Sep::Nop {
+                return Some(&self.c_reg);
+            }
+        }
+        self.run_tail(&mut || {});
+        None
+    }
+
+    fn run_segment(&mut self, idx: N, after: &mut dyn FnMut()) {
+        let (op, sep) = &self.q_ops.0[idx];
+        match *sep {
+            Sep::Nop => {
+                self.q_reg.apply_iter_with(op.iter().cloned(), after);
+            }
+            Sep::Measure(q_arg, c_arg) => {
+                self.q_reg.apply_iter_with(op.iter().cloned(), after);
+                self.measure(q_arg, c_arg);
+                self.history.push((idx, self.c_reg.clone()));
+            }
+            Sep::IfBranch(c, v) => {
+                if self.c_reg.get_by_mask(c) != v {
+                    op.iter().for_each(|_| after());
+                } else {
+                    self.q_reg.apply_iter_with(op.iter().cloned(), after);
                 }
+                self.history.push((idx, self.c_reg.clone()));
+            }
+            Sep::Reset(q) => {
+                self.q_reg.apply_iter_with(op.iter().cloned(), after);
+                self.q_reg.reset_by_mask(q);
             }
         }
-        self.q_reg.apply(&self.q_ops.1);
-        self
+    }
+
+    fn run_tail(&mut self, after: &mut dyn FnMut()) {
+        self.q_reg
+            .apply_iter_with(self.q_ops.1.iter().cloned(), after);
+        self.cursor = 0;
     }
 
     pub fn measure(&mut self, q_arg: N, c_arg: N) {
@@ -86,10 +191,39 @@ impl Sym {
         };
     }
 
+    /// Simulate circuit of *int* from the initial state in one call.
+    ///
+    /// It is a shortcut for [`init()`](Sym::init), [`reset()`](Sym::reset) and [`finish()`](Sym::finish),
+    /// which returns the final amplitudes of quantum register together with classical register.
+    ///
+    /// ```rust
+    /// # use qvnt::{prelude::*, qasm::Sym};
+    /// let ast = Ast::from_source("OPENQASM 2.0; qreg q[2]; creg c[1]; x q[1]; measure q[1] -> c[0];").unwrap();
+    /// let int = Int::new(ast).unwrap();
+    ///
+    /// let (psi, c_reg) = Sym::new(int.clone()).run(int);
+    /// assert_eq!(psi[0b10].re, 1.0);
+    /// assert_eq!(c_reg.get(), 1);
+    /// ```
+    pub fn run(&mut self, int: Int<'_>) -> (Vec<C>, CReg) {
+        self.init(int);
+        self.reset();
+        self.finish();
+        (self.q_reg.get_amplitudes(), self.get_class())
+    }
+
     pub fn get_class(&self) -> CReg {
         self.c_reg.clone()
     }
 
+    /// Snapshots of classical register, taken during [`finish()`](Sym::finish)
+    /// after each measurement and each conditional branch.
+    /// Every snapshot is paired with the index of the event in the operation tree.
+    /// History is cleared by [`reset()`](Sym::reset).
+    pub fn measurement_history(&self) -> Vec<(N, CReg)> {
+        self.history.clone()
+    }
+
     pub fn get_polar_wavefunction(&self) -> Vec<(R, R)> {
         self.q_reg.get_polar()
     }
@@ -1,6 +1,7 @@
 use std::{
     fmt,
     ops::{Mul, MulAssign},
+    str::FromStr,
 };
 
 use crate::math::types::*;
@@ -100,6 +101,29 @@ impl Reg {
         }
     }
 
+    /// Set the bit with a given index to a given value.
+    /// Indices outside of register are ignored.
+    pub fn set_bit(&mut self, index: N, value: bool) {
+        if index < self.q_num {
+            self.set(value, 1_usize << index);
+        }
+    }
+
+    /// Iterate over bits of classical register, starting from the least significant one.
+    /// Iterator always yields [`num()`](Reg::num) values.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let c = CReg::with_state(4, 0b0110);
+    /// let bits: Vec<bool> = c.iter_bits().collect();
+    ///
+    /// assert_eq!(bits, [false, true, true, false]);
+    /// ```
+    pub fn iter_bits(&self) -> impl Iterator<Item = bool> {
+        let value = self.value;
+        (0..self.q_num).map(move |idx| (value >> idx) & 1 != 0)
+    }
+
     fn tensor_prod(self, other: Self) -> Self {
         let shift = (0u8, self.q_num as u8);
         let state = (self.value << shift.0) | (other.value << shift.1);
@@ -139,6 +163,73 @@ impl fmt::Debug for Reg {
     }
 }
 
+/// Error of parsing [`CReg`](Reg) from string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// Character, which is not a binary digit, at a given position.
+    InvalidChar(N, char),
+    /// Number of bits exceeds the size of the machine word.
+    TooManyBits(N),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidChar(pos, ch) => {
+                write!(
+                    f,
+                    "Invalid character {ch:?} at position {pos}, expected '0' or '1'"
+                )
+            }
+            ParseError::TooManyBits(num) => {
+                write!(f, "Too many bits: {num}, at most {} are allowed", N::BITS)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parse classical register from bitstring, starting from the most significant bit.
+///
+/// Number of bits is the length of the string.
+/// Enclosing parentheses are allowed, so the [`Debug`](fmt::Debug) output is parsed back.
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// let c: CReg = "01101".parse().unwrap();
+/// assert_eq!(c, CReg::with_state(5, 0b01101));
+///
+/// assert_eq!("(01101)".parse::<CReg>().unwrap(), c);
+/// assert!("0121".parse::<CReg>().is_err());
+/// ```
+impl FromStr for Reg {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (offset, bits) = match s.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+            Some(bits) => (1, bits),
+            None => (0, s),
+        };
+
+        let q_num = bits.chars().count();
+        if q_num > N::BITS as N {
+            return Err(ParseError::TooManyBits(q_num));
+        }
+
+        let value = bits
+            .chars()
+            .enumerate()
+            .try_fold(0, |value, (pos, ch)| match ch {
+                '0' => Ok(value << 1),
+                '1' => Ok(value << 1 | 1),
+                _ => Err(ParseError::InvalidChar(pos + offset, ch)),
+            })?;
+
+        Ok(Self::with_state(q_num, value))
+    }
+}
+
 impl Mul for Reg {
     type Output = Self;
     fn mul(self, other: Self) -> Self {
@@ -162,4 +253,57 @@ mod tests {
 
         println!("{:?}", c);
     }
+
+    #[test]
+    fn iter_bits() {
+        let c = Reg::with_state(17, 123);
+
+        let value = c
+            .iter_bits()
+            .enumerate()
+            .fold(0, |acc, (idx, bit)| acc | ((bit as N) << idx));
+
+        assert_eq!(c.iter_bits().count(), 17);
+        assert_eq!(value, 123);
+    }
+
+    #[test]
+    fn from_str() {
+        for c in [
+            Reg::with_state(17, 123),
+            Reg::with_state(5, 0b01101),
+            Reg::new(1),
+            Reg::new(0),
+        ] {
+            assert_eq!(format!("{:?}", c).parse::<Reg>(), Ok(c));
+        }
+
+        assert_eq!(
+            "1".repeat(64).parse::<Reg>(),
+            Ok(Reg::with_state(64, N::MAX))
+        );
+        assert_eq!(
+            "1".repeat(65).parse::<Reg>(),
+            Err(ParseError::TooManyBits(65))
+        );
+        assert_eq!("01x1".parse::<Reg>(), Err(ParseError::InvalidChar(2, 'x')));
+        assert_eq!(
+            "(01 1)".parse::<Reg>(),
+            Err(ParseError::InvalidChar(3, ' '))
+        );
+        assert_eq!("(011".parse::<Reg>(), Err(ParseError::InvalidChar(0, '(')));
+    }
+
+    #[test]
+    fn set_bit() {
+        let mut c = Reg::new(4);
+
+        c.set_bit(1, true);
+        c.set_bit(3, true);
+        c.set_bit(4, true);
+        assert_eq!(c.get(), 0b1010);
+
+        c.set_bit(1, false);
+        assert_eq!(c.get(), 0b1000);
+    }
 }
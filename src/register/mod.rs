@@ -6,9 +6,13 @@
 //! * [`VReg`] - *vurtual* register.
 
 mod class;
+mod noise;
+mod pauli;
 mod quant;
 mod virtl;
 
-pub use class::Reg as CReg;
-pub use quant::Reg as QReg;
+pub use class::{ParseError as ParseCRegError, Reg as CReg};
+pub use noise::NoiseModel;
+pub use pauli::PauliString;
+pub use quant::{max_qubits, set_max_qubits, Endianness, Reg as QReg, MAX_QUBITS};
 pub use virtl::Reg as VReg;
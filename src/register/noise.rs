@@ -0,0 +1,130 @@
+use rand::prelude::*;
+
+use crate::{
+    math::{bits_iter::BitsIter, types::*},
+    operator::applicable::Noise,
+};
+
+/// Noise model for [`QReg`](super::QReg).
+///
+/// Noise model describes errors, which occur after each gate application.
+/// Currently, only *depolarizing* error is supported:
+/// with probability *p* each qubit, affected by a gate (including control qubits),
+/// is acted on by one of [`X`](crate::operator::x), [`Y`](crate::operator::y) or [`Z`](crate::operator::z)
+/// chosen with equal probabilities.
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// let mut q = QReg::with_noise(2, NoiseModel::new(0.01));
+///
+/// q.apply(&(op::h(0b01) * op::x(0b10).c(0b01).unwrap()));
+/// ```
+///
+/// Every run of noisy register gives only one random *trajectory* of quantum state.
+/// Averaging results over many trajectories approximates the evolution of the density matrix
+/// under the depolarizing channel.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NoiseModel {
+    depolarizing: R,
+}
+
+impl NoiseModel {
+    /// Create noise model with a given probability of depolarizing error per gate.
+    /// Probability is clamped to the range \[0, 1\].
+    pub fn new(depolarizing: R) -> Self {
+        Self {
+            depolarizing: depolarizing.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Probability of depolarizing error per gate.
+    pub fn depolarizing(&self) -> R {
+        self.depolarizing
+    }
+}
+
+impl Noise for NoiseModel {
+    fn apply(&self, act: N, psi: &mut [C]) {
+        if self.depolarizing == 0.0 {
+            return;
+        }
+
+        let mut rng = thread_rng();
+        for q in BitsIter::from(act) {
+            if !rng.gen_bool(crate::math::to_f64(self.depolarizing)) {
+                continue;
+            }
+            match rng.gen_range(0..3) {
+                0 => pauli_x(q, psi),
+                1 => pauli_y(q, psi),
+                _ => pauli_z(q, psi),
+            }
+        }
+    }
+}
+
+fn pauli_x(a_mask: N, psi: &mut [C]) {
+    for idx in (0..psi.len()).filter(|idx| idx & a_mask == 0) {
+        psi.swap(idx, idx | a_mask);
+    }
+}
+
+fn pauli_y(a_mask: N, psi: &mut [C]) {
+    for idx in (0..psi.len()).filter(|idx| idx & a_mask == 0) {
+        let (psi_0, psi_1) = (psi[idx], psi[idx | a_mask]);
+        psi[idx] = C::new(psi_1.im, -psi_1.re);
+        psi[idx | a_mask] = C::new(-psi_0.im, psi_0.re);
+    }
+}
+
+fn pauli_z(a_mask: N, psi: &mut [C]) {
+    psi.iter_mut()
+        .enumerate()
+        .filter(|(idx, _)| idx & a_mask != 0)
+        .for_each(|(_, psi)| *psi = -*psi);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn noiseless() {
+        let gate = op::h(0b01) * op::x(0b10).c(0b01).unwrap();
+
+        let mut q = QReg::new(2);
+        let mut noisy_q = QReg::with_noise(2, NoiseModel::new(0.0));
+
+        q.apply(&gate);
+        noisy_q.apply(&gate);
+
+        assert_eq!(q.get_probabilities(), noisy_q.get_probabilities());
+    }
+
+    #[test]
+    fn noise_after_each_gate() {
+        let mut q = QReg::with_noise(1, NoiseModel::new(1.0));
+        q.apply(&op::id());
+        assert_eq!(q.get_probabilities(), [1.0, 0.0]);
+        q.apply(&op::barrier(0b1));
+        assert_eq!(q.get_probabilities(), [1.0, 0.0]);
+
+        let ones = (0..600)
+            .filter(|_| {
+                let mut q = QReg::with_noise(1, NoiseModel::new(1.0));
+                q.apply(&op::x(0b1));
+                q.measure().get() == 1
+            })
+            .count();
+        assert!((100..300).contains(&ones));
+
+        let ones = (0..600)
+            .filter(|_| {
+                let mut q = QReg::with_noise(1, NoiseModel::new(1.0));
+                q.apply(&(op::x(0b1) * op::x(0b1)));
+                q.measure().get() == 1
+            })
+            .count();
+        assert!((150..350).contains(&ones));
+    }
+}
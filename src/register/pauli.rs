@@ -0,0 +1,92 @@
+use crate::math::types::*;
+
+/// Pauli string observable, i.e. tensor product of [`X`](crate::operator::x), [`Y`](crate::operator::y),
+/// [`Z`](crate::operator::z) and identity operators over qubits of register.
+///
+/// Pauli string is stored as a pair of masks: qubits with *X* part and qubits with *Z* part,
+/// where *Y* has both of them.
+/// It is evaluated by [`QReg::expectation_pauli`](super::QReg::expectation_pauli)
+/// and [`QReg::expectation_batch`](super::QReg::expectation_batch).
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// // X on qubit 0, Y on qubit 1, Z on qubit 3
+/// let p = PauliString::new(0b0001, 0b0010, 0b1000).unwrap();
+///
+/// assert_eq!(p.act_on(), 0b1011);
+/// assert_eq!(p, PauliString::x(0b0001) * PauliString::y(0b0010) * PauliString::z(0b1000));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PauliString {
+    x_mask: N,
+    z_mask: N,
+}
+
+impl PauliString {
+    /// Create Pauli string with *X*, *Y* and *Z* operators on qubits of corresponding masks.
+    /// Returns [`None`], if masks overlap.
+    pub fn new(x_mask: N, y_mask: N, z_mask: N) -> Option<Self> {
+        if x_mask & y_mask != 0 || y_mask & z_mask != 0 || z_mask & x_mask != 0 {
+            None
+        } else {
+            Some(Self {
+                x_mask: x_mask | y_mask,
+                z_mask: z_mask | y_mask,
+            })
+        }
+    }
+
+    /// Pauli string with *X* on every qubit of *mask*.
+    pub fn x(mask: N) -> Self {
+        Self {
+            x_mask: mask,
+            z_mask: 0,
+        }
+    }
+
+    /// Pauli string with *Y* on every qubit of *mask*.
+    pub fn y(mask: N) -> Self {
+        Self {
+            x_mask: mask,
+            z_mask: mask,
+        }
+    }
+
+    /// Pauli string with *Z* on every qubit of *mask*.
+    pub fn z(mask: N) -> Self {
+        Self {
+            x_mask: 0,
+            z_mask: mask,
+        }
+    }
+
+    /// Mask of qubits, on which Pauli string acts non-trivially.
+    pub fn act_on(&self) -> N {
+        self.x_mask | self.z_mask
+    }
+
+    pub(crate) fn masks(&self) -> (N, N) {
+        (self.x_mask, self.z_mask)
+    }
+}
+
+/// Tensor product of Pauli strings on disjoint qubits.
+///
+/// # Panics
+///
+/// Panics if Pauli strings act on the same qubits.
+impl std::ops::Mul for PauliString {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        assert_eq!(
+            self.act_on() & rhs.act_on(),
+            0,
+            "Pauli strings should act on different qubits!"
+        );
+        Self {
+            x_mask: self.x_mask | rhs.x_mask,
+            z_mask: self.z_mask | rhs.z_mask,
+        }
+    }
+}
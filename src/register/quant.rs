@@ -3,6 +3,7 @@
 use std::{
     fmt,
     ops::{Mul, MulAssign},
+    sync::atomic::{AtomicUsize, Ordering},
 };
 
 use rand::prelude::*;
@@ -15,6 +16,128 @@ use crate::math::{consts::*, types::*};
 const MIN_BUFFER_LEN: usize = 8;
 const MAX_LEN_TO_DISPLAY: usize = 8;
 
+/// Default maximal number of qubits of [`QReg`](Reg), see [`set_max_qubits`].
+///
+/// Register of *n* qubits stores 2<sup>n</sup> complex amplitudes of 16 bytes each (8 bytes with ```f32``` feature),
+/// and [`apply`](Reg::apply) needs one more buffer of the same size,
+/// so 30 qubits already require 32 GiB of memory (16 GiB in single precision).
+/// Creation of larger register panics with a clear message instead of aborting on allocation failure.
+pub const MAX_QUBITS: N = 30;
+
+static QUBIT_LIMIT: AtomicUsize = AtomicUsize::new(MAX_QUBITS);
+
+/// Current maximal number of qubits of [`QReg`](Reg), [`MAX_QUBITS`] by default.
+pub fn max_qubits() -> N {
+    QUBIT_LIMIT.load(Ordering::Relaxed)
+}
+
+/// Change maximal number of qubits of [`QReg`](Reg) for the whole program and return the previous limit.
+///
+/// Increase the limit, if machine has enough memory for larger registers,
+/// or decrease it to fail early in constrained environments.
+/// Returns [`None`] and keeps the limit, if *limit* is not less than number of bits in ```usize```.
+///
+/// ```rust
+/// # use qvnt::prelude::*;
+/// let default = set_max_qubits(20).unwrap();
+/// assert!(QReg::new(12).try_tensor_prod(QReg::new(12)).is_none());
+/// # assert_eq!(default, MAX_QUBITS);
+/// # set_max_qubits(default);
+/// ```
+pub fn set_max_qubits(limit: N) -> Option<N> {
+    if limit >= N::BITS as N {
+        None
+    } else {
+        Some(QUBIT_LIMIT.swap(limit, Ordering::Relaxed))
+    }
+}
+
+fn check_q_num(q_num: N) {
+    let limit = max_qubits();
+    assert!(
+        q_num <= limit,
+        "Requested {} qubits exceeds limit {} (would require {} GiB)",
+        q_num,
+        limit,
+        R::powi(2.0, q_num as i32 - 30) * (2 * std::mem::size_of::<C>()) as R
+    );
+}
+
+// Gather bits of *idx*, selected by *mask*, into the lowest bits.
+fn compress(idx: N, mask: N) -> N {
+    crate::math::bits_iter::BitsIter::from(mask)
+        .enumerate()
+        .fold(0, |acc, (jdx, bit)| {
+            if idx & bit != 0 {
+                acc | (1 << jdx)
+            } else {
+                acc
+            }
+        })
+}
+
+/// Order of qubits in indices of basis states, which are returned by quantum register.
+///
+/// By default, register uses [`Little`](Endianness::Little)-endian order:
+/// qubit 0 corresponds to the lowest bit of index, e.g. state |001> has index 1 after [`X`](crate::operator::x()) on qubit 0.
+/// With [`Big`](Endianness::Big)-endian order qubit 0 corresponds to the highest bit,
+/// so the bitstring reads from qubit 0 to the last one.
+///
+/// Endianness affects only the results of register, which are indexed by basis state
+/// (see [`QReg::set_endianness`](Reg::set_endianness)),
+/// while masks for gates and [`QReg::with_state`](Reg::with_state) are always little-endian.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+impl Endianness {
+    /// Convert index of basis state of *q_num* qubits between little-endian order and this one.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// assert_eq!(Endianness::Little.convert(0b0011, 4), 0b0011);
+    /// assert_eq!(Endianness::Big.convert(0b0011, 4), 0b1100);
+    /// ```
+    pub fn convert(self, idx: N, q_num: N) -> N {
+        match self {
+            Endianness::Little => idx,
+            Endianness::Big => idx
+                .reverse_bits()
+                .checked_shr(N::BITS - q_num as u32)
+                .unwrap_or(0),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct DisplayOptions {
+    max_entries: N,
+    precision: Option<N>,
+}
+
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        Self {
+            max_entries: MAX_LEN_TO_DISPLAY,
+            precision: None,
+        }
+    }
+}
+
+struct Amplitude<'a>(&'a C, Option<N>);
+
+impl fmt::Debug for Amplitude<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.1 {
+            Some(precision) => write!(f, "{:.*?}", precision, self.0),
+            None => fmt::Debug::fmt(self.0, f),
+        }
+    }
+}
+
 mod threading {
     #[derive(Clone, Copy, Debug)]
     pub enum Model {
@@ -91,7 +214,7 @@ mod threading {
 /// let mut q = QReg::new(2);
 /// # let gate = op::h(0b01) * op::x(0b10).c(0b01).unwrap();
 /// q.apply(&gate);
-/// let prob: Vec<f64> = q.get_probabilities();
+/// let prob: Vec<R> = q.get_probabilities();
 /// println!("{:?}", prob);
 /// # assert_eq!(prob, [0.5, 0.0, 0.0, 0.5]);
 /// ```
@@ -102,6 +225,12 @@ mod threading {
 #[derive(Clone)]
 pub struct Reg {
     th: threading::Model,
+    noise: Option<super::NoiseModel>,
+    display: DisplayOptions,
+    endianness: Endianness,
+    alias: Vec<Option<char>>,
+    history: Option<Vec<(N, super::CReg)>>,
+    pauli_frame: bool,
     psi: Vec<C>,
     q_num: N,
     q_mask: N,
@@ -110,7 +239,12 @@ pub struct Reg {
 impl Reg {
     /// Create quantum register with a given number of bits.
     /// Initial value will be set to 0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if *q_num* exceeds [`max_qubits`].
     pub fn new(q_num: N) -> Self {
+        check_q_num(q_num);
         let q_size = 1_usize << q_num;
 
         let mut psi = vec![C_ZERO; q_size.max(MIN_BUFFER_LEN)];
@@ -118,6 +252,12 @@ impl Reg {
 
         Self {
             th: threading::Single,
+            noise: None,
+            display: DisplayOptions::default(),
+            endianness: Endianness::default(),
+            alias: vec![],
+            history: None,
+            pauli_frame: false,
             psi,
             q_num,
             q_mask: q_size.wrapping_sub(1_usize),
@@ -126,7 +266,12 @@ impl Reg {
 
     /// Create quantum register with a given number of bits
     /// and an initial state
+    ///
+    /// # Panics
+    ///
+    /// Panics if *q_num* exceeds [`max_qubits`].
     pub fn with_state(q_num: N, state: N) -> Self {
+        check_q_num(q_num);
         let q_size = 1_usize << q_num;
         let q_mask = q_size.wrapping_sub(1_usize);
 
@@ -143,20 +288,220 @@ impl Reg {
 
         Self {
             th: threading::Single,
+            noise: None,
+            display: DisplayOptions::default(),
+            endianness: Endianness::default(),
+            alias: vec![],
+            history: None,
+            pauli_frame: false,
             psi,
             q_num,
             q_mask,
         }
     }
 
+    /// Create quantum register with the same number of bits as classical register *creg*
+    /// and the initial state equal to its value.
+    ///
+    /// It is useful to re-prepare the state, which was measured earlier:
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::with_state(4, 0b1010);
+    /// let c = q.measure();
+    ///
+    /// let q = QReg::from_creg(&c);
+    /// # assert_eq!(q.get_probabilities()[0b1010], 1.0);
+    /// ```
+    pub fn from_creg(creg: &super::CReg) -> Self {
+        Self::with_state(creg.num(), creg.get())
+    }
+
+    /// Create quantum register with a given number of bits
+    /// and an arbitrary initial state, defined by amplitudes *amps*.
+    /// Amplitudes are normalized, so only their ratios matter.
+    ///
+    /// Returns [`None`], if number of amplitudes is not equal to 2<sup>```q_num```</sup>
+    /// or all amplitudes are zero.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let amps = [C::new(1.0, 0.0), C::new(0.0, 0.0), C::new(0.0, 0.0), C::new(0.0, 1.0)];
+    /// let q = QReg::prepare(2, &amps).unwrap();
+    ///
+    /// assert!((q.get_probabilities()[0b00] - 0.5).abs() < 1e-12);
+    /// assert!((q.get_probabilities()[0b11] - 0.5).abs() < 1e-12);
+    /// assert!(QReg::prepare(3, &amps).is_none());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if *q_num* exceeds [`max_qubits`].
+    pub fn prepare(q_num: N, amps: &[C]) -> Option<Self> {
+        check_q_num(q_num);
+        let norm = amps.iter().map(C::norm_sqr).sum::<R>().sqrt();
+        if amps.len() != 1 << q_num || norm == 0. {
+            return None;
+        }
+
+        let mut q_reg = Self::new(q_num);
+        q_reg.psi[..amps.len()]
+            .iter_mut()
+            .zip(amps)
+            .for_each(|(psi, amp)| *psi = amp / norm);
+        Some(q_reg)
+    }
+
+    /// Create quantum register, which encodes real vector *data* into its amplitudes.
+    ///
+    /// Vector is padded with zeros to the next power of two 2<sup>n</sup>, which defines the number of qubits *n*,
+    /// and divided by its euclidean norm, so amplitude of state *i* is equal to ```data[i] / |data|```.
+    /// Returns [`None`], if all elements of *data* are zero or *data* is empty.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let q = QReg::amplitude_encode(&[3.0, 0.0, 4.0]).unwrap();
+    ///
+    /// assert_eq!(q.num(), 2);
+    /// # assert!((q.get_probabilities()[0b00] - 0.36).abs() < 1e-6);
+    /// # assert!((q.get_probabilities()[0b10] - 0.64).abs() < 1e-6);
+    /// ```
+    pub fn amplitude_encode(data: &[R]) -> Option<Self> {
+        let len = data.len().next_power_of_two();
+        let mut amps = vec![C_ZERO; len];
+        amps.iter_mut()
+            .zip(data)
+            .for_each(|(amp, &x)| *amp = C::from(x));
+
+        Self::prepare(len.trailing_zeros() as N, &amps)
+    }
+
+    /// Create quantum register with a given number of bits
+    /// in a uniform superposition of basis *states*.
+    ///
+    /// Every distinct state gets amplitude 1/&radic;k, where *k* is the number of distinct states,
+    /// and the rest of amplitudes are zero.
+    /// Returns [`None`], if *states* are empty or any of them doesn't fit into register.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let q = QReg::with_superposition(3, &[0b001, 0b010, 0b100]).unwrap();
+    ///
+    /// # assert!((q.get_probabilities()[0b010] - 1.0 / 3.0).abs() < 1e-12);
+    /// assert!(QReg::with_superposition(3, &[0b1000]).is_none());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if *q_num* exceeds [`max_qubits`].
+    pub fn with_superposition(q_num: N, states: &[N]) -> Option<Self> {
+        check_q_num(q_num);
+        let mut states = states.to_vec();
+        states.sort_unstable();
+        states.dedup();
+
+        match states.last() {
+            Some(&state) if state < 1_usize << q_num => {}
+            _ => return None,
+        }
+
+        let amp = C::from(1. / (states.len() as R).sqrt());
+        let mut q_reg = Self::new(q_num);
+        q_reg.psi[0] = C_ZERO;
+        states.into_iter().for_each(|state| q_reg.psi[state] = amp);
+        Some(q_reg)
+    }
+
+    /// Create quantum register with a given number of bits
+    /// and a [`noise model`](super::NoiseModel), which is used in every [`apply`](Reg::apply) call.
+    /// Initial value will be set to 0.
+    pub fn with_noise(q_num: N, noise: super::NoiseModel) -> Self {
+        Self {
+            noise: Some(noise),
+            ..Self::new(q_num)
+        }
+    }
+
     pub fn num(&self) -> N {
         self.q_num
     }
 
+    /// Set options for [`Debug`](fmt::Debug) output of register:
+    /// maximal number of displayed amplitudes and number of decimal places for them.
+    /// By default, only 8 amplitudes are displayed with full precision.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(4);
+    /// q.apply(&op::h(0b0001));
+    /// q.set_display_options(2, 3);
+    ///
+    /// assert_eq!(
+    ///     format!("{:?}", q),
+    ///     "QReg { 0: Complex { re: 0.707, im: 0.000 }, 1: Complex { re: 0.707, im: 0.000 }, .. }"
+    /// );
+    /// ```
+    pub fn set_display_options(&mut self, max_entries: N, precision: N) {
+        self.display = DisplayOptions {
+            max_entries,
+            precision: Some(precision),
+        };
+    }
+
+    /// Set [`order of qubits`](Endianness) in basis states, returned by register.
+    ///
+    /// It affects indexing of [`get_probabilities`](Reg::get_probabilities), [`get_polar`](Reg::get_polar),
+    /// [`sample_all`](Reg::sample_all) and [`sample_all_exact`](Reg::sample_all_exact),
+    /// labels of [`probability_histogram`](Reg::probability_histogram)
+    /// and values of [`argmax`](Reg::argmax), [`measure`](Reg::measure), [`measure_mask`](Reg::measure_mask)
+    /// and [`run_shots`](Reg::run_shots).
+    /// State of register and masks of gates are not affected.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(3);
+    /// q.apply(&op::x(0b001));
+    ///
+    /// assert_eq!(q.clone().measure().get(), 0b001);
+    ///
+    /// q.set_endianness(Endianness::Big);
+    /// assert_eq!(q.clone().measure().get(), 0b100);
+    /// assert_eq!(q.get_probabilities()[0b100], 1.0);
+    /// ```
+    pub fn set_endianness(&mut self, endianness: Endianness) {
+        self.endianness = endianness;
+    }
+
+    pub fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    // Convert index of basis state into the user-facing order.
+    fn convert(&self, idx: N) -> N {
+        self.endianness.convert(idx, self.q_num)
+    }
+
+    // Permute vector, indexed by basis states, into the user-facing order.
+    fn reorder<T: Copy>(&self, v: Vec<T>) -> Vec<T> {
+        match self.endianness {
+            Endianness::Little => v,
+            Endianness::Big => (0..v.len())
+                .map(|idx| {
+                    if idx <= self.q_mask {
+                        v[self.convert(idx)]
+                    } else {
+                        v[idx]
+                    }
+                })
+                .collect(),
+        }
+    }
+
     pub fn set_num(&mut self, q_num: N) {
         let q_size = 1_usize << q_num;
         self.q_num = q_num;
         self.q_mask = q_size.wrapping_sub(1_usize);
+        self.alias.truncate(q_num);
         self.psi.resize(q_size, C_ZERO);
 
         if q_num < self.q_num {
@@ -169,6 +514,7 @@ impl Reg {
         let q_size = 1_usize << q_num;
         self.q_num = q_num;
         self.q_mask = q_size.wrapping_sub(1_usize);
+        self.alias.truncate(q_num);
 
         if q_num < self.q_num {
             self.reset(0);
@@ -199,6 +545,49 @@ impl Reg {
         }
     }
 
+    /// Set specified number of threads for a given quantum register, if it is possible,
+    /// and fall back to single threading model otherwise.
+    ///
+    /// Unlike [`num_threads`](Reg::num_threads), this method never fails:
+    /// register stays single threaded, if *num_threads* is not available or thread pool could not be created,
+    /// so it is useful in constrained environments, e.g. CI containers with a few available threads.
+    /// Without "multi-thread" feature register always stays single threaded.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(4).num_threads_or_single(1024);
+    /// q.apply(&op::x(0b0101));
+    /// # assert_eq!(q.measure().get(), 0b0101);
+    /// ```
+    #[cfg_attr(not(feature = "multi-thread"), allow(unused_variables))]
+    pub fn num_threads_or_single(self, num_threads: usize) -> Self {
+        #[cfg(feature = "multi-thread")]
+        return self.num_threads_or_single_with(num_threads, rayon::ThreadPoolBuilder::new());
+
+        #[cfg(not(feature = "multi-thread"))]
+        Self {
+            th: threading::Single,
+            ..self
+        }
+    }
+
+    #[cfg(feature = "multi-thread")]
+    fn num_threads_or_single_with(
+        self,
+        num_threads: usize,
+        builder: rayon::ThreadPoolBuilder,
+    ) -> Self {
+        let th = if num_threads > 1
+            && num_threads <= rayon::current_num_threads()
+            && crate::threads::try_set_num_threads(num_threads, builder).is_ok()
+        {
+            threading::Multi(num_threads)
+        } else {
+            threading::Single
+        };
+        Self { th, ..self }
+    }
+
     pub(crate) fn reset(&mut self, i_state: N) {
         self.psi = vec![C_ZERO; self.psi.len()];
         self.psi[self.q_mask & i_state] = C_ONE;
@@ -233,6 +622,39 @@ impl Reg {
         super::VReg::new_with_mask(self.q_mask)
     }
 
+    /// Name qubits of register by characters of *alias*.
+    ///
+    /// The i-th character labels the i-th qubit (from the lowest one), so qubits with the same character
+    /// form a named part of register, which is obtained by [`get_vreg_by_char`](Reg::get_vreg_by_char).
+    /// Characters beyond the number of qubits are ignored.
+    /// Aliases of both registers are kept by tensor product.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(5);
+    /// q.set_alias("xxyyz");
+    ///
+    /// let x = q.get_vreg_by_char('x').unwrap();
+    /// q.apply(&op::h(x[..]));
+    /// # assert_eq!(x[..], 0b00011);
+    /// ```
+    pub fn set_alias(&mut self, alias: &str) {
+        self.alias = alias.chars().take(self.q_num).map(Some).collect();
+    }
+
+    /// Acquire the [`VReg`](super::VReg) for qubits, named by character *c* via [`set_alias`](Reg::set_alias).
+    ///
+    /// Returns [`None`], if there are no such qubits.
+    pub fn get_vreg_by_char(&self, c: char) -> Option<super::VReg> {
+        let mask = self
+            .alias
+            .iter()
+            .enumerate()
+            .filter(|(_, alias)| **alias == Some(c))
+            .fold(0, |mask, (idx, _)| mask | 1 << idx);
+        (mask != 0).then(|| super::VReg::new_with_mask(mask))
+    }
+
     /// Acquire the [`VReg`](super::VReg) for a specified part of quantum register.
     pub fn get_vreg_by(&self, mask: N) -> Option<super::VReg> {
         if mask & !self.q_mask != 0 {
@@ -328,7 +750,23 @@ impl Reg {
     }
 
     fn tensor_prod(self, other: Self) -> Self {
+        check_q_num(self.q_num + other.q_num);
+
         let th = self.th.and(other.th);
+        let noise = self.noise.or(other.noise);
+        let display = self.display;
+        let endianness = self.endianness;
+        let history = self.history.clone();
+        let pauli_frame = self.pauli_frame;
+        let alias = match (self.alias.is_empty(), other.alias.is_empty()) {
+            (true, true) => vec![],
+            _ => {
+                let mut alias = self.alias.clone();
+                alias.resize(self.q_num, None);
+                alias.extend_from_slice(&other.alias);
+                alias
+            }
+        };
 
         let shift = (0u8, self.q_num as u8);
         let mask = (self.q_mask, other.q_mask);
@@ -364,362 +802,2684 @@ impl Reg {
 
         Self {
             th,
+            noise,
+            display,
+            endianness,
+            alias,
+            history,
+            pauli_frame,
             psi,
             q_num,
             q_mask: q_size.wrapping_sub(1_usize),
         }
     }
 
+    /// Tensor product of 2 registers, which also returns the bit offset of *other*'s qubits.
+    ///
+    /// Qubits of *self* keep their indices, while qubit with index *i* of *other*
+    /// is addressed as *i + offset* in the combined register.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let (mut q, offset) = QReg::new(2).tensor_prod_tracked(QReg::new(3));
+    ///
+    /// // Apply X gate to the first qubit of second register
+    /// q.apply(&op::x(0b1 << offset));
+    /// # assert_eq!(q.measure().get(), 0b00100);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the combined register exceeds [`max_qubits`], see [`try_tensor_prod`](Reg::try_tensor_prod).
+    pub fn tensor_prod_tracked(self, other: Self) -> (Self, N) {
+        let offset = self.q_num;
+        (self.tensor_prod(other), offset)
+    }
+
+    /// Tensor product of 2 registers, which never panics.
+    ///
+    /// Unlike ```*```, returns [`None`], if the combined register would exceed [`max_qubits`].
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// assert!(QReg::new(2).try_tensor_prod(QReg::new(3)).is_some());
+    /// assert!(QReg::new(20).try_tensor_prod(QReg::new(20)).is_none());
+    /// ```
+    pub fn try_tensor_prod(self, other: Self) -> Option<Self> {
+        if self.q_num + other.q_num > max_qubits() {
+            None
+        } else {
+            Some(self.tensor_prod(other))
+        }
+    }
+
+    /// Tensor product of several registers, folded from left to right.
+    ///
+    /// Qubits of the first register get the lowest indices, as for repeated ```*```.
+    /// Empty collection gives register with no qubits.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let q = QReg::tensor_many(vec![QReg::with_state(1, 1); 3]);
+    /// # assert_eq!(q.num(), 3);
+    /// # assert_eq!(q.get_probabilities()[0b111], 1.0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the combined register exceeds [`max_qubits`].
+    pub fn tensor_many<I>(regs: I) -> Self
+    where
+        I: IntoIterator<Item = Self>,
+    {
+        regs.into_iter()
+            .reduce(Self::tensor_prod)
+            .unwrap_or_else(|| Self::new(0))
+    }
+
     /// Apply quantum gate to register.
     /// This method only works in single threading model.
     /// To accelerate it you may use [`apply_sync`].
+    ///
+    /// If register was created [`with_noise`](Reg::with_noise),
+    /// noise is applied after each gate.
+    ///
+    /// In debug builds it is checked that gate acts only on existing qubits.
+    /// Use [`try_apply`](Reg::try_apply) to handle it without panic.
     pub fn apply<Op>(&mut self, op: &Op)
     where
         Op: crate::operator::applicable::Applicable,
     {
+        debug_assert!(
+            op.act_on() & !self.q_mask == 0,
+            "Gate acts on qubits {:#b}, which are out of {}-qubit register!",
+            op.act_on() & !self.q_mask,
+            self.q_num
+        );
+
+        // barriers and identities leave state as is, so it is not even copied
+        if let Some(mut gates) = op.gates() {
+            if gates.all(|gate| gate.is_identity()) {
+                return;
+            }
+        }
+
+        if self.pauli_frame && self.noise.is_none() {
+            if let Some(gates) = op.gates() {
+                self.apply_iter_with(gates.cloned(), || {});
+                return;
+            }
+        }
+
         match self.th {
             threading::Single => {
                 let mut psi = Vec::with_capacity(self.psi.capacity());
                 unsafe { psi.set_len(self.psi.len()) };
-                op.apply(&self.psi, &mut psi);
+                match &self.noise {
+                    Some(noise) => op.apply_noisy(&self.psi, &mut psi, noise),
+                    None => op.apply(&self.psi, &mut psi),
+                }
                 std::mem::swap(&mut self.psi, &mut psi);
             }
             #[cfg(feature = "multi-thread")]
             threading::Multi(n) => crate::threads::global_install(n, || {
                 let mut psi = Vec::with_capacity(self.psi.capacity());
                 unsafe { psi.set_len(self.psi.len()) };
-                op.apply_sync(&self.psi, &mut psi);
+                match &self.noise {
+                    Some(noise) => op.apply_sync_noisy(&self.psi, &mut psi, noise),
+                    None => op.apply_sync(&self.psi, &mut psi),
+                }
                 std::mem::swap(&mut self.psi, &mut psi);
             }),
         }
     }
 
-    fn normalize(&mut self) -> &mut Self {
-        let norm = self.get_absolute().sqrt();
-        if norm <= 1e-15 {
-            self.reset(0);
-            return self;
-        } else if 1. - norm <= 1e-9 {
-            return self;
+    /// Apply quantum gate to register, if it acts only on existing qubits.
+    ///
+    /// Otherwise register is left unchanged and mask of non-existent qubits is returned as an error.
+    /// This catches gates, applied to qubits beyond the register, e.g. ```op::h(0b1000)``` on 3 qubits.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(3);
+    ///
+    /// assert_eq!(q.try_apply(&op::h(0b0100)), Ok(()));
+    /// assert_eq!(q.try_apply(&op::h(0b1100)), Err(0b1000));
+    /// ```
+    pub fn try_apply<Op>(&mut self, op: &Op) -> Result<(), N>
+    where
+        Op: crate::operator::applicable::Applicable,
+    {
+        match op.act_on() & !self.q_mask {
+            0 => {
+                self.apply(op);
+                Ok(())
+            }
+            out_of_range => Err(out_of_range),
         }
-        let norm = 1. / norm;
+    }
+
+    /// Apply quantum gate to a copy of register state, storing result in *out* and leaving this register unchanged.
+    ///
+    /// Amplitude buffer of *out* is reused, so repeated speculative application of gates
+    /// (e.g. in branch-and-bound search) doesn't allocate a new register each time.
+    /// *out* must have the same number of qubits, otherwise this method panics.
+    /// Threading model, noise and endianness of *out* are replaced by those of this register.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let q = QReg::new(2);
+    /// let mut out = QReg::new(2);
+    ///
+    /// q.apply_into(&op::x(0b01), &mut out);
+    /// # assert_eq!(q.get_probabilities()[0b00], 1.0);
+    /// # assert_eq!(out.get_probabilities()[0b01], 1.0);
+    /// ```
+    pub fn apply_into<Op>(&self, op: &Op, out: &mut Self)
+    where
+        Op: crate::operator::applicable::Applicable,
+    {
+        assert_eq!(
+            out.q_num, self.q_num,
+            "Output register has different number of qubits!"
+        );
+
+        out.th = self.th;
+        out.noise = self.noise;
+        out.endianness = self.endianness;
+
         match self.th {
-            threading::Single => self.psi.iter_mut().for_each(|v| *v *= norm),
+            threading::Single => match &self.noise {
+                Some(noise) => op.apply_noisy(&self.psi, &mut out.psi, noise),
+                None => op.apply(&self.psi, &mut out.psi),
+            },
             #[cfg(feature = "multi-thread")]
-            threading::Multi(n) => crate::threads::global_install(n, || {
-                self.psi.par_iter_mut().for_each(|v| *v *= norm)
+            threading::Multi(n) => crate::threads::global_install(n, || match &self.noise {
+                Some(noise) => op.apply_sync_noisy(&self.psi, &mut out.psi, noise),
+                None => op.apply_sync(&self.psi, &mut out.psi),
             }),
-        };
+        }
+    }
+
+    /// Apply quantum gate to register and return it.
+    ///
+    /// This is the by-value version of [`apply`](Reg::apply),
+    /// which makes it possible to build short circuits fluently:
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let q = QReg::new(2)
+    ///     .then(&op::h(0b01))
+    ///     .then(&op::x(0b10).c(0b01).unwrap());
+    /// # assert_eq!(q.get_probabilities(), [0.5, 0.0, 0.0, 0.5]);
+    /// ```
+    pub fn then<Op>(mut self, op: &Op) -> Self
+    where
+        Op: crate::operator::applicable::Applicable,
+    {
+        self.apply(op);
         self
     }
 
-    /// Return complex amplitudes of quantum states of register in polar form.
-    pub fn get_polar(&self) -> Vec<(R, R)> {
-        match self.th {
-            threading::Single => self.psi[..(1 << self.q_num)]
-                .iter()
-                .map(|z| z.to_polar())
-                .collect(),
-            #[cfg(feature = "multi-thread")]
-            threading::Multi(n) => crate::threads::global_install(n, || {
-                self.psi[..(1 << self.q_num)]
-                    .par_iter()
-                    .map(|z| z.to_polar())
-                    .collect()
-            }),
+    /// Return a new register with quantum gate applied, leaving this one unchanged.
+    ///
+    /// This is the out-of-place version of [`apply`](Reg::apply),
+    /// which is useful for branching, when the original state should be preserved:
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let q = QReg::new(1).then(&op::h(0b1));
+    /// let (q0, q1) = (q.applied(&op::h(0b1)), q.applied(&op::z(0b1)));
+    /// # assert_eq!(q0.get_probabilities(), [1.0, 0.0]);
+    /// # assert!((q1.get_probabilities()[0] - 0.5).abs() < 1e-9);
+    /// ```
+    pub fn applied<Op>(&self, op: &Op) -> Self
+    where
+        Op: crate::operator::applicable::Applicable,
+    {
+        self.clone().then(op)
+    }
+
+    /// Apply classical reversible function to register as an oracle.
+    ///
+    /// Amplitude of basis state |idx> is moved to |f(idx)>,
+    /// so *f* must be a bijection over 0..2<sup>N</sup>, where N is the number of qubits.
+    /// This is the simple way to build boolean oracles (e.g. for Grover's search)
+    /// without decomposing them into [`X`](crate::operator::x) and Toffoli gates.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::with_state(3, 0b011);
+    ///
+    /// // Increment modulo 8
+    /// q.apply_oracle(|idx| (idx + 1) % 8);
+    /// # assert_eq!(q.measure().get(), 0b100);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if *f* maps any state out of register. In debug builds also panics if *f* is not a bijection,
+    /// while in release builds such *f* silently leaves register in an unnormalized state.
+    /// Register is left unchanged on panic.
+    pub fn apply_oracle<F>(&mut self, f: F)
+    where
+        F: Fn(N) -> N,
+    {
+        let q_size = 1_usize << self.q_num;
+
+        let mut psi = vec![C_ZERO; self.psi.len()];
+        #[cfg(debug_assertions)]
+        let mut visited = vec![false; q_size];
+        for idx in 0..q_size {
+            let jdx = f(idx);
+            assert!(jdx < q_size, "Oracle maps state out of register!");
+            #[cfg(debug_assertions)]
+            assert!(
+                !std::mem::replace(&mut visited[jdx], true),
+                "Oracle should be a bijection!"
+            );
+            psi[jdx] = self.psi[idx];
         }
+        self.psi = psi;
     }
 
-    /// Return probabilities of quantum states of register.
-    pub fn get_probabilities(&self) -> Vec<R> {
-        match self.th {
-            threading::Single => {
-                let abs: R = self.psi.iter().map(|z| z.norm_sqr()).sum();
-                let abs = 1. / abs;
-                self.psi[..(1 << self.q_num)]
-                    .iter()
-                    .map(|z| z.norm_sqr() * abs)
-                    .collect()
+    /// Apply phase oracle to register.
+    ///
+    /// Amplitude of every basis state |idx>, for which *predicate* holds, is multiplied by -1.
+    /// This is the marking step of Grover's search.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(2);
+    /// q.apply(&op::h(0b11));
+    ///
+    /// // Mark |11> state
+    /// q.apply_phase_oracle(|idx| idx == 0b11);
+    /// q.apply(&op::grover_diffusion(0b11));
+    /// # assert!((q.get_probabilities()[0b11] - 1.0).abs() < 1e-9);
+    /// ```
+    pub fn apply_phase_oracle<F>(&mut self, predicate: F)
+    where
+        F: Fn(N) -> bool + Sync,
+    {
+        let q_size = 1_usize << self.q_num;
+        let flip = |(idx, z): (N, &mut C)| {
+            if predicate(idx) {
+                *z = -*z;
             }
+        };
+
+        match self.th {
+            threading::Single => self.psi[..q_size].iter_mut().enumerate().for_each(flip),
             #[cfg(feature = "multi-thread")]
             threading::Multi(n) => crate::threads::global_install(n, || {
-                let abs: R = self.psi.par_iter().map(|z| z.norm_sqr()).sum();
-                let abs = 1. / abs;
-                self.psi[..(1 << self.q_num)]
-                    .par_iter()
-                    .map(|z| z.norm_sqr() * abs)
-                    .collect()
+                self.psi[..q_size].par_iter_mut().enumerate().for_each(flip)
             }),
         }
     }
 
-    /// Return absolute value of wavefunction of quantum register.
-    /// If you use gates from [`op`](crate::operator) module, it always will be 1.
-    pub fn get_absolute(&self) -> R {
+    /// Apply phase, depending on index of basis state.
+    ///
+    /// Amplitude of every basis state |idx> is multiplied by ```exp(i·phase_fn(idx))```.
+    /// This is a diagonal operation, which generalizes [`apply_phase_oracle`](Reg::apply_phase_oracle)
+    /// to arbitrary phases, e.g. for arithmetic phase oracles.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(3);
+    /// q.apply(&op::h(0b111));
+    ///
+    /// // Flip phase of indices divisible by 3
+    /// q.apply_index_phase(|idx| if idx % 3 == 0 { std::f64::consts::PI as R } else { 0.0 });
+    /// ```
+    pub fn apply_index_phase<F>(&mut self, phase_fn: F)
+    where
+        F: Fn(N) -> R + Sync,
+    {
+        let q_size = 1_usize << self.q_num;
+        let rotate = |(idx, z): (N, &mut C)| *z *= C::from_polar(1., phase_fn(idx));
+
         match self.th {
-            threading::Single => self.psi.iter().map(|z| z.norm_sqr()).sum(),
+            threading::Single => self.psi[..q_size].iter_mut().enumerate().for_each(rotate),
             #[cfg(feature = "multi-thread")]
             threading::Multi(n) => crate::threads::global_install(n, || {
-                self.psi.par_iter().map(|z| z.norm_sqr()).sum()
+                self.psi[..q_size]
+                    .par_iter_mut()
+                    .enumerate()
+                    .for_each(rotate)
             }),
         }
     }
 
-    fn collapse_mask(&mut self, idy: N, mask: N) {
+    /// Apply a stream of quantum gates to register, one by one.
+    ///
+    /// Gates are consumed as they are produced, so long circuits could be generated lazily
+    /// without building the whole [`MultiOp`](crate::operator::MultiOp) first.
+    /// The same buffer is reused for every gate.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(2);
+    ///
+    /// q.apply_iter((0..10).map(|_| op::x(0b11)[0].clone()));
+    /// # assert_eq!(q.get_probabilities()[0], 1.0);
+    /// ```
+    pub fn apply_iter<I>(&mut self, ops: I)
+    where
+        I: IntoIterator<Item = crate::operator::SingleOp>,
+    {
+        self.apply_iter_with(ops, || {});
+    }
+
+    /// Same as [`apply_iter`](Reg::apply_iter), but *after* is called after every gate.
+    pub(crate) fn apply_iter_with<I, F>(&mut self, ops: I, mut after: F)
+    where
+        I: IntoIterator<Item = crate::operator::SingleOp>,
+        F: FnMut(),
+    {
+        use crate::operator::{applicable::Applicable, FrameUpdate, PauliFrame};
+
+        let mut psi = Vec::with_capacity(self.psi.capacity());
+        unsafe { psi.set_len(self.psi.len()) };
+
+        let track = self.pauli_frame && self.noise.is_none();
+        let mut frame = PauliFrame::default();
+
+        for op in ops {
+            if op.is_identity() {
+                after();
+                continue;
+            }
+            if track {
+                match frame.update(&op) {
+                    FrameUpdate::Absorbed => {
+                        after();
+                        continue;
+                    }
+                    FrameUpdate::Conjugated => {}
+                    FrameUpdate::Blocked => {
+                        self.apply_pauli_frame(frame, &mut psi);
+                        frame = PauliFrame::default();
+                    }
+                }
+            }
+            match self.th {
+                threading::Single => match &self.noise {
+                    Some(noise) => op.apply_noisy(&self.psi, &mut psi, noise),
+                    None => op.apply(&self.psi, &mut psi),
+                },
+                #[cfg(feature = "multi-thread")]
+                threading::Multi(n) => crate::threads::global_install(n, || match &self.noise {
+                    Some(noise) => op.apply_sync_noisy(&self.psi, &mut psi, noise),
+                    None => op.apply_sync(&self.psi, &mut psi),
+                }),
+            }
+            std::mem::swap(&mut self.psi, &mut psi);
+            after();
+        }
+        self.apply_pauli_frame(frame, &mut psi);
+    }
+
+    fn apply_pauli_frame(&mut self, frame: crate::operator::PauliFrame, psi: &mut Vec<C>) {
+        if frame.is_identity() {
+            return;
+        }
+
+        let psi_i = &self.psi;
+        match self.th {
+            threading::Single => psi
+                .iter_mut()
+                .enumerate()
+                .for_each(|(idx, z)| *z = frame.amplitude(psi_i, idx)),
+            #[cfg(feature = "multi-thread")]
+            threading::Multi(n) => crate::threads::global_install(n, || {
+                psi.par_iter_mut()
+                    .enumerate()
+                    .for_each(|(idx, z)| *z = frame.amplitude(psi_i, idx))
+            }),
+        }
+        std::mem::swap(&mut self.psi, psi);
+    }
+
+    /// Apply operation, compiled by [`MultiOp::compile`](crate::operator::MultiOp::compile).
+    ///
+    /// Result is the same as for [`apply`](Reg::apply) of the source operation,
+    /// but compiled one skips identity gates and merges runs of diagonal gates,
+    /// which pays off if the same circuit is applied many times.
+    /// If register has a noise model, source operation is applied gate by gate instead.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let oracle = op::z(0b10).c(0b01).unwrap();
+    /// let iteration = (oracle * op::grover_diffusion(0b11)).compile(2);
+    ///
+    /// let mut q = QReg::new(2);
+    /// q.apply(&op::h(0b11));
+    /// q.apply_compiled(&iteration);
+    /// # assert!((q.get_probabilities()[0b11] - 1.0).abs() < 1e-9);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if operation was compiled for different number of qubits.
+    pub fn apply_compiled(&mut self, op: &crate::operator::CompiledOp) {
+        assert_eq!(
+            op.q_num(),
+            self.q_num,
+            "Operation was compiled for different number of qubits!"
+        );
+
+        if self.noise.is_some() {
+            return self.apply(op.source());
+        }
+
+        let mut psi = Vec::with_capacity(self.psi.capacity());
+        unsafe { psi.set_len(self.psi.len()) };
+
+        match self.th {
+            threading::Single => op.apply(&mut self.psi, &mut psi),
+            #[cfg(feature = "multi-thread")]
+            threading::Multi(n) => {
+                crate::threads::global_install(n, || op.apply_sync(&mut self.psi, &mut psi))
+            }
+        }
+    }
+
+    /// Apply layer of gates, acting on disjoint qubits, in a single pass over register.
+    ///
+    /// Since gates of the layer commute, the result is the same as for their sequential [`apply`](Reg::apply),
+    /// but every amplitude is updated once for the whole layer, using the tensor product of gates' matrices.
+    /// Its size grows exponentially with the number of qubits, so layers acting on more than
+    /// 6 qubits in total, as well as layers on registers with a noise model, are applied gate by gate.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let layer = (op::h(0b001) * op::x(0b100).c(0b010).unwrap())
+    ///     .iter()
+    ///     .cloned()
+    ///     .collect::<Vec<_>>();
+    ///
+    /// let mut q = QReg::new(3);
+    /// q.apply_layer(&layer);
+    /// # assert!((q.get_probabilities()[0b001] - 0.5).abs() < 1e-12);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if gates act on overlapping qubits.
+    pub fn apply_layer(&mut self, gates: &[crate::operator::SingleOp]) {
+        use crate::{math::bits_iter::BitsIter, operator::Applicable};
+
+        const MAX_FUSED_QUBITS: u32 = 6;
+
+        let layer_mask = gates.iter().try_fold(0, |mask, gate| {
+            (mask & gate.act_on() == 0).then_some(mask | gate.act_on())
+        });
+        let layer_mask = layer_mask.expect("Gates of layer should act on disjoint qubits!");
+
+        if self.noise.is_some() || layer_mask.count_ones() > MAX_FUSED_QUBITS {
+            return gates.iter().for_each(|gate| self.apply(gate));
+        }
+
+        let offsets = (0..1 << layer_mask.count_ones())
+            .map(|local: N| {
+                BitsIter::from(layer_mask)
+                    .enumerate()
+                    .filter(|(idx, _)| local >> idx & 1 != 0)
+                    .fold(0, |offset, (_, bit)| offset | bit)
+            })
+            .collect::<Vec<_>>();
+        let matrices = gates
+            .iter()
+            .map(|gate| (gate.act_on(), gate.local_matrix()))
+            .collect::<Vec<_>>();
+
+        // Sparse rows of the tensor product of gates' matrices
+        let rows = offsets
+            .iter()
+            .map(|&row| {
+                offsets
+                    .iter()
+                    .map(|&col| {
+                        let coef = matrices
+                            .iter()
+                            .map(|(mask, matrix)| {
+                                matrix[compress(row, *mask)][compress(col, *mask)]
+                            })
+                            .product::<C>();
+                        (col, coef)
+                    })
+                    .filter(|(_, coef)| coef.norm_sqr() > 0.)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let psi_i = &self.psi;
+        let amplitude = |idx: N| {
+            let base = idx & !layer_mask;
+            rows[compress(idx, layer_mask)]
+                .iter()
+                .map(|&(col, coef)| coef * psi_i[base | col])
+                .sum()
+        };
+
+        let psi = match self.th {
+            threading::Single => (0..psi_i.len()).map(amplitude).collect(),
+            #[cfg(feature = "multi-thread")]
+            threading::Multi(n) => crate::threads::global_install(n, || {
+                (0..psi_i.len()).into_par_iter().map(amplitude).collect()
+            }),
+        };
+        self.psi = psi;
+    }
+
+    fn normalize(&mut self) -> &mut Self {
+        let norm = self.get_absolute().sqrt();
+        if norm <= 1e-15 {
+            self.reset(0);
+            return self;
+        } else if 1. - norm <= 1e-9 {
+            return self;
+        }
+        let norm = 1. / norm;
+        match self.th {
+            threading::Single => self.psi.iter_mut().for_each(|v| *v *= norm),
+            #[cfg(feature = "multi-thread")]
+            threading::Multi(n) => crate::threads::global_install(n, || {
+                self.psi.par_iter_mut().for_each(|v| *v *= norm)
+            }),
+        };
+        self
+    }
+
+    /// Return complex amplitudes of quantum states of register.
+    pub fn get_amplitudes(&self) -> Vec<C> {
+        self.reorder(self.psi[..(1 << self.q_num)].to_vec())
+    }
+
+    /// Return complex amplitudes of quantum states of register in polar form.
+    pub fn get_polar(&self) -> Vec<(R, R)> {
+        let polar = match self.th {
+            threading::Single => self.psi[..(1 << self.q_num)]
+                .iter()
+                .map(|z| z.to_polar())
+                .collect(),
+            #[cfg(feature = "multi-thread")]
+            threading::Multi(n) => crate::threads::global_install(n, || {
+                self.psi[..(1 << self.q_num)]
+                    .par_iter()
+                    .map(|z| z.to_polar())
+                    .collect()
+            }),
+        };
+        self.reorder(polar)
+    }
+
+    /// Return probabilities of quantum states of register.
+    pub fn get_probabilities(&self) -> Vec<R> {
+        self.reorder(self.probabilities())
+    }
+
+    fn probabilities(&self) -> Vec<R> {
         match self.th {
             threading::Single => {
-                self.psi.iter_mut().enumerate().for_each(|(idx, psi)| {
-                    if (idx ^ idy) & mask != 0 {
-                        *psi = C_ZERO;
-                    }
-                });
+                let abs: R = self.psi.iter().map(|z| z.norm_sqr()).sum();
+                let abs = 1. / abs;
+                self.psi[..(1 << self.q_num)]
+                    .iter()
+                    .map(|z| z.norm_sqr() * abs)
+                    .collect()
             }
             #[cfg(feature = "multi-thread")]
             threading::Multi(n) => crate::threads::global_install(n, || {
-                self.psi.par_iter_mut().enumerate().for_each(|(idx, psi)| {
-                    if (idx ^ idy) & mask != 0 {
-                        *psi = C_ZERO;
-                    }
-                });
+                let abs: R = self.psi.par_iter().map(|z| z.norm_sqr()).sum();
+                let abs = 1. / abs;
+                self.psi[..(1 << self.q_num)]
+                    .par_iter()
+                    .map(|z| z.norm_sqr() * abs)
+                    .collect()
+            }),
+        }
+    }
+
+    /// Return marginal probabilities of measurement outcomes of qubits in *mask*.
+    ///
+    /// Result has 2<sup>k</sup> entries, where ```k = mask.count_ones()```,
+    /// and the lowest qubit of *mask* corresponds to the lowest bit of index.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(3);
+    /// q.apply(&(op::h(0b001) * op::x(0b100)));
+    ///
+    /// assert_eq!(q.probabilities_of(0b101), [0.0, 0.0, 0.5, 0.5]);
+    /// ```
+    pub fn probabilities_of(&self, mask: N) -> Vec<R> {
+        let mask = mask & self.q_mask;
+        let mut marginal = vec![0.; 1 << mask.count_ones()];
+        self.probabilities()
+            .into_iter()
+            .enumerate()
+            .for_each(|(idx, p)| marginal[compress(idx, mask)] += p);
+        marginal
+    }
+
+    /// Return marginal probabilities of qubits in *mask* as pairs of bitstring label and probability,
+    /// ready to be plotted as histogram.
+    ///
+    /// Labels are formatted as [`Debug`](std::fmt::Debug) output of [`CReg`](super::CReg)
+    /// with the most significant bit first, and entries are ordered by label.
+    /// Bits of label follow [`endianness`](Reg::set_endianness) of register.
+    /// If *skip_zeros* is set, outcomes with zero probability are omitted.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(3);
+    /// q.apply(&(op::h(0b001) * op::x(0b100)));
+    ///
+    /// let hist = q.probability_histogram(0b101, true);
+    /// assert_eq!(hist, [("(10)".to_string(), 0.5), ("(11)".to_string(), 0.5)]);
+    /// ```
+    pub fn probability_histogram(&self, mask: N, skip_zeros: bool) -> Vec<(String, R)> {
+        let bits = (mask & self.q_mask).count_ones() as N;
+        let marginal = self.probabilities_of(mask);
+        (0..marginal.len())
+            .map(|idx| (idx, marginal[self.endianness.convert(idx, bits)]))
+            .filter(|(_, p)| !skip_zeros || *p > 0.)
+            .map(|(idx, p)| (format!("{:?}", super::CReg::with_state(bits, idx)), p))
+            .collect()
+    }
+
+    /// Return Shannon entropy ```-Σ p log2 p``` (in bits) of measurement outcomes of qubits in *mask*.
+    ///
+    /// Unlike entanglement entropy, it depends on the measurement basis:
+    /// it is ```k``` for uniform distribution over ```k``` qubits and ```0``` for any basis state.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(3);
+    /// q.apply(&op::h(0b011));
+    ///
+    /// assert!((q.measurement_entropy(0b111) - 2.0).abs() < 1e-9);
+    /// ```
+    pub fn measurement_entropy(&self, mask: N) -> R {
+        -self
+            .probabilities_of(mask)
+            .into_iter()
+            .filter(|&p| p > 0.)
+            .map(|p| p * p.log2())
+            .sum::<R>()
+    }
+
+    /// Return the most probable basis state of register and its probability, without collapsing the state.
+    /// If several states are equally probable, the lowest one is returned.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(2);
+    /// q.apply(&(op::ry(1.0, 0b01) * op::x(0b10)));
+    ///
+    /// let (state, prob) = q.argmax();
+    /// assert_eq!(state, 0b10);
+    /// # assert!((prob - (0.5 as R).cos().powi(2)).abs() < 1e-9);
+    /// ```
+    pub fn argmax(&self) -> (N, R) {
+        fn max((i, a): (N, R), (j, b): (N, R)) -> (N, R) {
+            if b > a || (b == a && j < i) {
+                (j, b)
+            } else {
+                (i, a)
+            }
+        }
+
+        let psi = &self.psi[..(1 << self.q_num)];
+        let (idx, prob) = match self.th {
+            threading::Single => psi
+                .iter()
+                .enumerate()
+                .map(|(idx, z)| (idx, z.norm_sqr()))
+                .fold((0, -1.), max),
+            #[cfg(feature = "multi-thread")]
+            threading::Multi(n) => crate::threads::global_install(n, || {
+                psi.par_iter()
+                    .enumerate()
+                    .map(|(idx, z)| (idx, z.norm_sqr()))
+                    .reduce(|| (0, -1.), max)
+            }),
+        };
+        (self.convert(idx), prob / self.get_absolute())
+    }
+
+    /// Return absolute value of wavefunction of quantum register.
+    /// If you use gates from [`op`](crate::operator) module, it always will be 1.
+    pub fn get_absolute(&self) -> R {
+        match self.th {
+            threading::Single => self.psi.iter().map(|z| z.norm_sqr()).sum(),
+            #[cfg(feature = "multi-thread")]
+            threading::Multi(n) => crate::threads::global_install(n, || {
+                self.psi.par_iter().map(|z| z.norm_sqr()).sum()
+            }),
+        }
+    }
+
+    /// Compare states of two registers up to a global phase.
+    ///
+    /// Both wavefunctions are normalized, then phase of the second one is aligned by the first significant amplitude
+    /// (which is greater than *tol* by absolute value) and amplitudes are compared element-wise with tolerance *tol*.
+    /// Registers with a different number of qubits are never equal.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q1 = QReg::new(1);
+    /// let mut q2 = QReg::new(1);
+    ///
+    /// // Z·X = i·Y, so states differ only by a global phase
+    /// q1.apply(&(op::x(0b1) * op::z(0b1)));
+    /// q2.apply(&op::y(0b1));
+    ///
+    /// assert!(q1.approx_eq(&q2, 1e-9));
+    /// ```
+    pub fn approx_eq(&self, other: &Self, tol: R) -> bool {
+        use crate::math::approx_cmp::approx_eq_complex_eps;
+
+        if self.q_num != other.q_num {
+            return false;
+        }
+
+        let len = 1 << self.q_num;
+        let norm = (
+            1.0 / self.get_absolute().sqrt(),
+            1.0 / other.get_absolute().sqrt(),
+        );
+        let psi = (&self.psi[..len], &other.psi[..len]);
+
+        let first = |psi: &[C], norm: R| psi.iter().position(|z| z.norm() * norm > tol);
+        let phase = match (first(psi.0, norm.0), first(psi.1, norm.1)) {
+            (Some(i), Some(j)) if i == j => {
+                let phase = psi.1[i] * psi.0[i].conj();
+                phase / phase.norm()
+            }
+            (None, None) => C_ONE,
+            _ => return false,
+        };
+
+        psi.0
+            .iter()
+            .zip(psi.1)
+            .all(|(a, b)| approx_eq_complex_eps(&(a * phase * norm.0), &(b * norm.1), tol))
+    }
+
+    /// Return expectation value of Pauli Z string over qubits in *mask*,
+    /// i.e. ```<Z⊗...⊗Z>```.
+    ///
+    /// Since observable is diagonal, it is computed directly from probabilities of basis states:
+    /// each state contributes with sign ```-1```, if it has odd number of ones in *mask*, and ```+1``` otherwise.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(2);
+    /// q.apply(&(op::h(0b01) * op::cnot(0b01, 0b10)));
+    ///
+    /// assert_eq!(q.expectation_z(0b11), 1.0);
+    /// # assert_eq!(q.expectation_z(0b01), 0.0);
+    /// ```
+    pub fn expectation_z(&self, mask: N) -> R {
+        let term = |(idx, z): (N, &C)| {
+            if (idx & mask).count_ones() & 1 == 1 {
+                -z.norm_sqr()
+            } else {
+                z.norm_sqr()
+            }
+        };
+
+        let psi = &self.psi[..(1 << self.q_num)];
+        match self.th {
+            threading::Single => psi.iter().enumerate().map(term).sum::<R>() / self.get_absolute(),
+            #[cfg(feature = "multi-thread")]
+            threading::Multi(n) => crate::threads::global_install(n, || {
+                psi.par_iter().enumerate().map(term).sum::<R>() / self.get_absolute()
+            }),
+        }
+    }
+
+    /// Return expectation value ```<ψ|P|ψ>``` of [`Pauli string`](super::PauliString) *P*.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(2);
+    /// q.apply(&(op::h(0b01) * op::cnot(0b01, 0b10)));
+    ///
+    /// // Bell state is stabilized by XX and -YY
+    /// assert!((q.expectation_pauli(&PauliString::x(0b11)) - 1.0).abs() < 1e-9);
+    /// assert!((q.expectation_pauli(&PauliString::y(0b11)) + 1.0).abs() < 1e-9);
+    /// ```
+    pub fn expectation_pauli(&self, observable: &super::PauliString) -> R {
+        self.pauli_term(observable) / self.get_absolute()
+    }
+
+    /// Return expectation values of several [`Pauli strings`](super::PauliString) at once.
+    ///
+    /// Norm of state is computed only once for all *observables*,
+    /// and with "multi-thread" feature observables are evaluated in parallel.
+    /// Result is equal to calling [`expectation_pauli`](Reg::expectation_pauli) for each observable.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(2);
+    /// q.apply(&op::h(0b11));
+    ///
+    /// let hamiltonian = [PauliString::x(0b01), PauliString::z(0b11), PauliString::x(0b11)];
+    /// let energy = q.expectation_batch(&hamiltonian);
+    /// # assert!((energy[0] - 1.0).abs() < 1e-9 && energy[1].abs() < 1e-9 && (energy[2] - 1.0).abs() < 1e-9);
+    /// ```
+    pub fn expectation_batch(&self, observables: &[super::PauliString]) -> Vec<R> {
+        let abs = self.get_absolute();
+        match self.th {
+            threading::Single => observables
+                .iter()
+                .map(|obs| self.pauli_term(obs) / abs)
+                .collect(),
+            #[cfg(feature = "multi-thread")]
+            threading::Multi(n) => crate::threads::global_install(n, || {
+                observables
+                    .par_iter()
+                    .map(|obs| self.pauli_term(obs) / abs)
+                    .collect()
             }),
         }
     }
 
-    /// Measure specified qubits into classical register.
-    /// Wavefunction of quantum register will collapse after measurement.
-    pub fn measure_mask(&mut self, mask: N) -> super::CReg {
-        let mask = mask & self.q_mask;
-        if mask == 0 {
-            return super::CReg::new(self.q_num);
+    // Unnormalized <ψ|P|ψ> = i^|x&z| Σ conj(ψ[idx ^ x]) ψ[idx] (-1)^|idx & z|
+    fn pauli_term(&self, observable: &super::PauliString) -> R {
+        let (x_mask, z_mask) = observable.masks();
+        let (x_mask, z_mask) = (x_mask & self.q_mask, z_mask & self.q_mask);
+
+        let psi = &self.psi[..(1 << self.q_num)];
+        let sum = psi
+            .iter()
+            .enumerate()
+            .map(|(idx, z)| {
+                let term = psi[idx ^ x_mask].conj() * z;
+                if (idx & z_mask).count_ones() & 1 == 1 {
+                    -term
+                } else {
+                    term
+                }
+            })
+            .sum::<C>();
+
+        match (x_mask & z_mask).count_ones() & 3 {
+            0 => sum.re,
+            1 => -sum.im,
+            2 => -sum.re,
+            _ => sum.im,
+        }
+    }
+
+    /// Return correlation coefficient between parities of measurement outcomes of qubits in *mask_a* and *mask_b*.
+    ///
+    /// Parity of each mask is treated as random variable with values ```+1``` and ```-1```,
+    /// so coefficient equals ```(<Za Zb> - <Za><Zb>) / sqrt((1 - <Za>^2)(1 - <Zb>^2))```,
+    /// where ```Za``` and ```Zb``` are [`Pauli Z strings`](Reg::expectation_z) over corresponding masks.
+    /// It lies between ```-1``` and ```1```, and equals ```0```, if either of parities is deterministic.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(2);
+    /// q.apply(&(op::h(0b01) * op::cnot(0b01, 0b10)));
+    ///
+    /// assert!((q.correlation(0b01, 0b10) - 1.0).abs() < 1e-9);
+    /// ```
+    pub fn correlation(&self, mask_a: N, mask_b: N) -> R {
+        let (z_a, z_b) = (self.expectation_z(mask_a), self.expectation_z(mask_b));
+        let z_ab = self.expectation_z(mask_a ^ mask_b);
+
+        let variance = (1. - z_a * z_a) * (1. - z_b * z_b);
+        if variance <= 1e-15 {
+            0.
+        } else {
+            (z_ab - z_a * z_b) / variance.sqrt()
+        }
+    }
+
+    /// Return Bloch vector ```(<X>, <Y>, <Z>)``` of the qubit with a given index.
+    ///
+    /// Vector is computed from the reduced density matrix of qubit,
+    /// so it has unit length only if qubit is not entangled with the rest of register.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(2);
+    /// q.apply(&op::h(0b01));
+    ///
+    /// assert_eq!(q.bloch_vector(1), (0.0, 0.0, 1.0));
+    /// # let (x, y, z) = q.bloch_vector(0);
+    /// # assert!((x - 1.0).abs() < 1e-9 && y.abs() < 1e-9 && z.abs() < 1e-9);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if *qubit_idx* is not less than number of qubits.
+    pub fn bloch_vector(&self, qubit_idx: N) -> (R, R, R) {
+        let (rho_00, rho_11, rho_01) = self.reduced_density_matrix(qubit_idx);
+        (2.0 * rho_01.re, -2.0 * rho_01.im, rho_00 - rho_11)
+    }
+
+    /// Return expectation value ```Tr(ρ·obs)``` of Hermitian 1-qubit observable *obs*
+    /// for the qubit with a given index, where ```ρ``` is the reduced density matrix of qubit.
+    ///
+    /// Returns [`None`], if *obs* is not Hermitian.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(1);
+    /// q.apply(&op::x(0b1));
+    ///
+    /// let z = [C::new(1.0, 0.0), C::new(0.0, 0.0), C::new(0.0, 0.0), C::new(-1.0, 0.0)];
+    /// assert_eq!(q.expectation_observable(0, z), Some(-1.0));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if *qubit_idx* is not less than number of qubits.
+    pub fn expectation_observable(&self, qubit_idx: N, obs: M1) -> Option<R> {
+        if !crate::math::matrix::is_hermitian_m1(&obs) {
+            return None;
+        }
+
+        let (rho_00, rho_11, rho_01) = self.reduced_density_matrix(qubit_idx);
+        Some(rho_00 * obs[0b00].re + rho_11 * obs[0b11].re + 2.0 * (rho_01 * obs[0b10]).re)
+    }
+
+    fn reduced_density_matrix(&self, qubit_idx: N) -> (R, R, C) {
+        assert!(qubit_idx < self.q_num, "Qubit index is out of range!");
+
+        let mask = 1_usize << qubit_idx;
+        let psi = &self.psi[..(1 << self.q_num)];
+
+        let (mut rho_00, mut rho_11, mut rho_01) = (0.0, 0.0, C_ZERO);
+        for (idx, z) in psi.iter().enumerate() {
+            if idx & mask == 0 {
+                rho_00 += z.norm_sqr();
+                rho_01 += z * psi[idx | mask].conj();
+            } else {
+                rho_11 += z.norm_sqr();
+            }
+        }
+
+        let norm = rho_00 + rho_11;
+        (rho_00 / norm, rho_11 / norm, rho_01 / norm)
+    }
+
+    /// Return reduced density matrix of qubits in *mask*, tracing out the rest of register.
+    ///
+    /// Matrix has 2<sup>k</sup> &times; 2<sup>k</sup> elements, where ```k = mask.count_ones()```,
+    /// and the lowest qubit of *mask* corresponds to the lowest bit of row and column indices,
+    /// as in [`probabilities_of`](Reg::probabilities_of).
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(2);
+    /// q.apply(&(op::h(0b01) * op::cnot(0b01, 0b10)));
+    ///
+    /// // Qubit of Bell pair is maximally mixed
+    /// let rho = q.reduced_density_matrix_of(0b10);
+    /// assert!((rho[0][0].re - 0.5).abs() < 1e-12 && rho[0][1].norm() < 1e-12);
+    /// ```
+    pub fn reduced_density_matrix_of(&self, mask: N) -> Vec<Vec<C>> {
+        let (dim, rho) = self.reduced_density_matrix_flat(mask);
+        rho.chunks(dim).map(<[C]>::to_vec).collect()
+    }
+
+    /// Return dimension and reduced density matrix of qubits in *mask*, flattened in row-major order,
+    /// i.e. element ```(row, col)``` is at index ```row * dim + col```.
+    ///
+    /// It is the same matrix as [`reduced_density_matrix_of`](Reg::reduced_density_matrix_of),
+    /// but a single contiguous buffer is easier to pass across FFI boundary.
+    /// Since [`Complex`](num_complex::Complex) is ```#[repr(C)]```, the buffer is laid out
+    /// as interleaved real and imaginary parts, like ```double _Complex``` in C or ```complex128``` in NumPy.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(2);
+    /// q.apply(&op::h(0b01));
+    ///
+    /// let (dim, rho) = q.reduced_density_matrix_flat(0b01);
+    /// assert_eq!((dim, rho.len()), (2, 4));
+    /// # assert!(rho.iter().all(|z| (z.re - 0.5).abs() < 1e-12 && z.im.abs() < 1e-12));
+    /// ```
+    pub fn reduced_density_matrix_flat(&self, mask: N) -> (N, Vec<C>) {
+        let (rows, cols) = (mask & self.q_mask, !mask & self.q_mask);
+        let dim = 1 << rows.count_ones();
+
+        let mut psi = vec![vec![C_ZERO; dim]; 1 << cols.count_ones()];
+        self.psi[..(1 << self.q_num)]
+            .iter()
+            .enumerate()
+            .for_each(|(idx, z)| psi[compress(idx, cols)][compress(idx, rows)] = *z);
+
+        let mut rho = vec![C_ZERO; dim * dim];
+        for psi in &psi {
+            for (row, z_row) in psi.iter().enumerate() {
+                for (col, z_col) in psi.iter().enumerate() {
+                    rho[row * dim + col] += z_row * z_col.conj();
+                }
+            }
+        }
+
+        let norm = self.get_absolute();
+        rho.iter_mut().for_each(|z| *z /= norm);
+        (dim, rho)
+    }
+
+    /// Drop amplitudes of quantum states, which probabilities are less than *threshold*,
+    /// and renormalize the rest of wavefunction.
+    ///
+    /// This is the way to reduce effective support of the state for approximate simulation.
+    /// Note that truncation introduces error, which is bounded by the total dropped probability,
+    /// so *threshold* should be chosen with care.
+    /// If all amplitudes are dropped, register will be reset to |0>.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(1);
+    /// q.apply(&op::rx(0.01, 0b1));
+    /// q.truncate(1e-3);
+    ///
+    /// assert_eq!(q.get_probabilities(), [1.0, 0.0]);
+    /// ```
+    pub fn truncate(&mut self, threshold: R) {
+        let truncate = |z: &mut C| {
+            if z.norm_sqr() < threshold {
+                *z = C_ZERO;
+            }
+        };
+
+        match self.th {
+            threading::Single => self.psi.iter_mut().for_each(truncate),
+            #[cfg(feature = "multi-thread")]
+            threading::Multi(n) => {
+                crate::threads::global_install(n, || self.psi.par_iter_mut().for_each(truncate))
+            }
+        };
+        self.normalize();
+    }
+
+    /// Return the Schmidt rank of the state across the bipartition,
+    /// given by the *mask* and its complement.
+    ///
+    /// Schmidt rank is the number of singular values of the coefficient matrix
+    /// ```psi[a, b]```, which are greater than *tol*,
+    /// where *a* and *b* are indices of the 2 parts of register.
+    /// Product state has rank 1, while entangled states have rank greater than 1.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(2);
+    /// q.apply(&(op::h(0b01) * op::x(0b10).c(0b01).unwrap()));
+    ///
+    /// assert_eq!(q.schmidt_rank(0b01, 1e-9), 2);
+    /// ```
+    pub fn schmidt_rank(&self, mask: N, tol: R) -> usize {
+        let mask = (mask & self.q_mask, !mask & self.q_mask);
+        let (rows, cols) = if mask.0.count_ones() < mask.1.count_ones() {
+            (mask.1, mask.0)
+        } else {
+            (mask.0, mask.1)
+        };
+
+        let mut matrix = vec![vec![C_ZERO; 1 << rows.count_ones()]; 1 << cols.count_ones()];
+        self.psi[..(1 << self.q_num)]
+            .iter()
+            .enumerate()
+            .for_each(|(idx, psi)| matrix[compress(idx, cols)][compress(idx, rows)] = *psi);
+
+        crate::math::matrix::singular_values(matrix)
+            .into_iter()
+            .filter(|&s| s > tol)
+            .count()
+    }
+
+    fn collapse_mask(&mut self, idy: N, mask: N) {
+        match self.th {
+            threading::Single => {
+                self.psi.iter_mut().enumerate().for_each(|(idx, psi)| {
+                    if (idx ^ idy) & mask != 0 {
+                        *psi = C_ZERO;
+                    }
+                });
+            }
+            #[cfg(feature = "multi-thread")]
+            threading::Multi(n) => crate::threads::global_install(n, || {
+                self.psi.par_iter_mut().enumerate().for_each(|(idx, psi)| {
+                    if (idx ^ idy) & mask != 0 {
+                        *psi = C_ZERO;
+                    }
+                });
+            }),
+        }
+    }
+
+    /// Collapse specified qubits onto the given *value* instead of a random outcome
+    /// and renormalize wavefunction. Only bits of *value*, which are set in *mask*, are used.
+    ///
+    /// Returns probability of the chosen outcome,
+    /// or [`None`], if it is impossible (register is not changed in this case).
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(2);
+    /// q.apply(&(op::h(0b01) * op::cnot(0b01, 0b10)));
+    ///
+    /// // postselect the first qubit of Bell state to |1>
+    /// let prob = q.postselect(0b01, 0b01).unwrap();
+    /// # assert!((prob - 0.5).abs() < 1e-9);
+    /// # assert_eq!(q.get_probabilities(), [0.0, 0.0, 0.0, 1.0]);
+    /// ```
+    pub fn postselect(&mut self, mask: N, value: N) -> Option<R> {
+        let mask = mask & self.q_mask;
+        let prob: R = self.psi[..(1 << self.q_num)]
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| (idx ^ value) & mask == 0)
+            .map(|(_, z)| z.norm_sqr())
+            .sum::<R>()
+            / self.get_absolute();
+
+        if prob <= 1e-15 {
+            return None;
+        }
+
+        self.collapse_mask(value, mask);
+        self.normalize();
+        Some(prob)
+    }
+
+    /// Track Pauli gates as a *Pauli frame* instead of applying them to the state vector.
+    ///
+    /// During [`apply`](Reg::apply) and [`apply_iter`](Reg::apply_iter) [`X`](crate::operator::x()),
+    /// [`Y`](crate::operator::y()) and [`Z`](crate::operator::z()) gates are merged into the frame,
+    /// which is commuted symbolically through [`H`](crate::operator::h()), [`S`](crate::operator::s()),
+    /// [`CNOT`](crate::operator::cnot()), controlled [`Z`](crate::operator::z()) and [`SWAP`](crate::operator::swap()) gates,
+    /// and through any gate on other qubits.
+    /// The frame is applied to the state vector in one pass only before other gates, which do not commute with it,
+    /// and at the end of operation, so the state of register is always the same as without tracking.
+    /// It speeds up circuits with many Pauli corrections, e.g. teleportation or error correction.
+    /// Pauli frame is not used for registers [`with_noise`](Reg::with_noise).
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let circuit = op::h(0b01) * op::x(0b01) * op::cnot(0b01, 0b10) * op::z(0b10) * op::x(0b11);
+    ///
+    /// let mut q = QReg::new(2);
+    /// q.enable_pauli_frame();
+    /// q.apply(&circuit);
+    ///
+    /// assert!(q.approx_eq(&QReg::new(2).then(&circuit), 1e-12));
+    /// ```
+    pub fn enable_pauli_frame(&mut self) {
+        self.pauli_frame = true;
+    }
+
+    /// Start recording outcomes of [`measure`](Reg::measure) and [`measure_mask`](Reg::measure_mask) calls.
+    ///
+    /// History is off by default. Calling this method again clears already recorded outcomes.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(2);
+    /// q.enable_history();
+    /// q.apply(&op::h(0b01));
+    ///
+    /// let c = q.measure_mask(0b01);
+    /// assert_eq!(q.history(), [(0b01, c)]);
+    /// ```
+    pub fn enable_history(&mut self) {
+        self.history = Some(vec![]);
+    }
+
+    /// Recorded pairs of measured mask and outcome in order of measurements.
+    /// Empty, if history is not [`enabled`](Reg::enable_history).
+    pub fn history(&self) -> &[(N, super::CReg)] {
+        self.history.as_deref().unwrap_or_default()
+    }
+
+    /// Measure specified qubits into classical register.
+    /// Wavefunction of quantum register will collapse after measurement.
+    pub fn measure_mask(&mut self, mask: N) -> super::CReg {
+        self.measure_mask_with_prob(mask).0
+    }
+
+    /// Measure specified qubits into classical register
+    /// and return probability of the obtained outcome, computed before collapse.
+    /// Wavefunction of quantum register will collapse after measurement.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(2);
+    /// q.apply(&(op::h(0b01) * op::x(0b10)));
+    ///
+    /// let (c, prob) = q.measure_mask_with_prob(0b11);
+    /// assert!(c.get() == 0b10 || c.get() == 0b11);
+    /// assert!((prob - 0.5).abs() < 1e-12);
+    /// ```
+    pub fn measure_mask_with_prob(&mut self, mask: N) -> (super::CReg, R) {
+        let mask = mask & self.q_mask;
+        let (creg, prob) = if mask == 0 {
+            (super::CReg::new(self.q_num), 1.)
+        } else {
+            let probs = self.probabilities();
+            let rand_idx = thread_rng().sample(rand_distr::WeightedIndex::new(&probs).unwrap());
+            let prob = probs
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| (idx ^ rand_idx) & mask == 0)
+                .map(|(_, p)| p)
+                .sum();
+
+            self.collapse_mask(rand_idx, mask);
+            (
+                super::CReg::with_state(self.q_num, self.convert(rand_idx & mask)),
+                prob,
+            )
+        };
+
+        if let Some(history) = &mut self.history {
+            history.push((mask, creg.clone()));
+        }
+        (creg, prob)
+    }
+
+    /// Measure all qubits into classical register.
+    /// Wavefunction of quantum register will collapse after measurement.
+    pub fn measure(&mut self) -> super::CReg {
+        self.measure_mask(self.q_mask)
+    }
+
+    /// Apply quantum gate to a copy of register and measure it *shots* times.
+    ///
+    /// Gate is applied only once, and all measurements are drawn independently from the final state,
+    /// so this method is much faster, than calling [`measure`](Reg::measure) on a fresh clone for every shot.
+    /// Register itself stays untouched.
+    pub fn run_shots<Op>(&self, op: &Op, shots: N) -> Vec<super::CReg>
+    where
+        Op: crate::operator::applicable::Applicable,
+    {
+        let mut q_reg = self.clone();
+        q_reg.apply(op);
+
+        let q_num = q_reg.q_num;
+        let dist = rand_distr::WeightedIndex::new(q_reg.get_probabilities()).unwrap();
+
+        match q_reg.th {
+            threading::Single => {
+                let mut rng = thread_rng();
+                (0..shots)
+                    .map(|_| super::CReg::with_state(q_num, rng.sample(&dist)))
+                    .collect()
+            }
+            #[cfg(feature = "multi-thread")]
+            threading::Multi(n) => crate::threads::global_install(n, || {
+                (0..shots)
+                    .into_par_iter()
+                    .map_init(thread_rng, |rng, _| {
+                        super::CReg::with_state(q_num, rng.sample(&dist))
+                    })
+                    .collect()
+            }),
+        }
+    }
+
+    /// Make an exact histogram for quantum register.
+    ///
+    /// Unlike [`sample_all`](Reg::sample_all), which uses gaussian approximation,
+    /// counts are drawn from the multinomial distribution as a chain of binomial draws.
+    /// Histogram is unbiased and always sums up to *count*, but it is slower for large registers.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(3);
+    /// q.apply(&op::h(0b011));
+    ///
+    /// let hist = q.sample_all_exact(1000);
+    /// assert_eq!(hist.iter().sum::<usize>(), 1000);
+    /// # assert_eq!(hist[0b100..].iter().sum::<usize>(), 0);
+    /// ```
+    pub fn sample_all_exact(&self, count: N) -> Vec<N> {
+        let p = self.get_probabilities();
+        let last = p.len() - 1;
+
+        let mut rng = thread_rng();
+        let (mut count, mut p_rest) = (count, 1.0);
+        p.into_iter()
+            .enumerate()
+            .map(|(idx, p)| {
+                let n = if idx == last {
+                    count
+                } else if count == 0 || p_rest <= 0.0 {
+                    0
+                } else {
+                    let p = (p / p_rest).clamp(0.0, 1.0);
+                    rng.sample(
+                        rand_distr::Binomial::new(count as u64, crate::math::to_f64(p)).unwrap(),
+                    ) as N
+                };
+                count -= n;
+                p_rest -= p;
+                n
+            })
+            .collect()
+    }
+
+    /// Estimate expectation value of Pauli Z string over qubits in *mask* from *shots* measurements,
+    /// as it is done on a real quantum computer.
+    ///
+    /// Returns sampled mean and its standard error ```sqrt((1 - mean^2) / shots)```.
+    /// Outcomes are drawn by [`sample_all_exact`](Reg::sample_all_exact), so register is not collapsed.
+    /// The exact value is given by [`expectation_z`](Reg::expectation_z).
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let mut q = QReg::new(1);
+    /// q.apply(&op::ry(1.0, 0b1));
+    ///
+    /// let (mean, error) = q.sample_expectation_z(0b1, 10_000);
+    /// assert!((mean - q.expectation_z(0b1)).abs() < 5.0 * error);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if *shots* is zero.
+    pub fn sample_expectation_z(&self, mask: N, shots: N) -> (R, R) {
+        assert_ne!(shots, 0, "Number of shots should be positive!");
+
+        // histogram is indexed in the user-facing order
+        let mask = self.convert(mask & self.q_mask);
+        let sum = self
+            .sample_all_exact(shots)
+            .into_iter()
+            .enumerate()
+            .map(|(idx, n)| {
+                if (idx & mask).count_ones() & 1 == 1 {
+                    -(n as Z)
+                } else {
+                    n as Z
+                }
+            })
+            .sum::<Z>();
+
+        let mean = sum as R / shots as R;
+        (mean, ((1. - mean * mean).max(0.) / shots as R).sqrt())
+    }
+
+    /// Make a histogram for quantum register.
+    /// This histogram also could be obtained by calling [`measure`](Reg::measure) *count* times.
+    /// But [`sample_all`](Reg::sample_all) does not collapse wavefunction and executes __MUSH FASTER__.
+    /// If you want to simulate the execution of quantum computer, you would prefer [`sample_all`](Reg::sample_all).
+    pub fn sample_all(&self, count: N) -> Vec<N> {
+        use std::cmp::Ordering;
+
+        let p = self.get_probabilities();
+        let c = count as R;
+        let c_sqrt = c.sqrt();
+
+        let (mut n, delta) = match self.th {
+            threading::Single => {
+                let mut rng = rand::thread_rng();
+                let n = p
+                    .iter()
+                    .map(|&p| {
+                        let rnd: R = rng.sample(rand_distr::StandardNormal);
+                        p.sqrt() * rnd
+                    })
+                    .collect::<Vec<R>>();
+
+                let n_sum = n.iter().sum::<R>();
+
+                let n = (0..self.psi.len())
+                    .map(|idx| {
+                        ((c * p[idx] + c_sqrt * (n[idx] - n_sum * p[idx])).round() as Z).max(0) as N
+                    })
+                    .collect::<Vec<N>>();
+
+                let delta = n.iter().sum::<N>() as Z - count as Z;
+
+                (n, delta)
+            }
+            #[cfg(feature = "multi-thread")]
+            threading::Multi(n) => crate::threads::global_install(n, || {
+                let n = p
+                    .par_iter()
+                    .map(|&p| {
+                        let rnd: R = rand::thread_rng().sample(rand_distr::StandardNormal);
+                        p.sqrt() * rnd
+                    })
+                    .collect::<Vec<R>>();
+
+                let n_sum = n.par_iter().sum::<R>();
+
+                let n = (0..self.psi.len())
+                    .map(|idx| {
+                        ((c * p[idx] + c_sqrt * (n[idx] - n_sum * p[idx])).round() as Z).max(0) as N
+                    })
+                    .collect::<Vec<N>>();
+
+                let delta = n.par_iter().sum::<N>() as Z - count as Z;
+
+                (n, delta)
+            }),
+        };
+        match delta.cmp(&0) {
+            Ordering::Less => {
+                let delta = delta.unsigned_abs();
+                let delta = (delta >> self.q_num, delta % self.q_mask);
+                for (idx, n) in n.iter_mut().enumerate() {
+                    *n += delta.0;
+                    if idx < delta.1 {
+                        *n += 1;
+                    }
+                }
+            }
+            Ordering::Greater => {
+                let mut delta = delta as N;
+                for idx in 0.. {
+                    if delta == 0 {
+                        break;
+                    }
+                    if n[idx & self.q_mask] == 0 {
+                        continue;
+                    }
+                    n[idx & self.q_mask] -= 1;
+                    delta -= 1;
+                }
+            }
+            _ => {}
+        }
+
+        n
+    }
+}
+
+impl Default for Reg {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl fmt::Debug for Reg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let DisplayOptions {
+            max_entries,
+            precision,
+        } = self.display;
+        if 1 << self.q_num <= max_entries {
+            self.psi[..(1 << self.q_num)]
+                .iter()
+                .enumerate()
+                .fold(&mut f.debug_struct("QReg"), |f, (idx, psi)| {
+                    f.field(&format!("{}", idx), &Amplitude(psi, precision))
+                })
+                .finish()
+        } else {
+            self.psi[..max_entries]
+                .iter()
+                .enumerate()
+                .fold(&mut f.debug_struct("QReg"), |f, (idx, psi)| {
+                    f.field(&format!("{}", idx), &Amplitude(psi, precision))
+                })
+                .finish_non_exhaustive()
+        }
+    }
+}
+
+impl Mul for Reg {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        self.tensor_prod(other)
+    }
+}
+
+impl MulAssign for Reg {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = std::mem::take(self).tensor_prod(rhs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        math::{consts::*, types::*},
+        prelude::*,
+    };
+
+    #[test]
+    fn quantum_reg() {
+        let mut reg = QReg::with_state(4, 0b1100);
+        let mask = 0b0110;
+
+        let operator =
+            op::h(0b1111) * op::h(0b0011).c(0b1000).unwrap() * op::swap(0b1001).c(0b0010).unwrap();
+
+        reg.apply(&operator);
+
+        assert_eq!(format!("{:?}", operator), "[H3, H12, C8_H3, C2_SWAP9]");
+        assert_eq!(
+            reg.psi,
+            [
+                C { re: 0.25, im: 0.0 },
+                C { re: 0.25, im: 0.0 },
+                C { re: 0.25, im: 0.0 },
+                C { re: 0.0, im: 0.0 },
+                C { re: -0.25, im: 0.0 },
+                C { re: -0.25, im: 0.0 },
+                C { re: -0.25, im: 0.0 },
+                C { re: 0.0, im: 0.0 },
+                C { re: -0.5, im: 0.0 },
+                C { re: 0.0, im: 0.0 },
+                C { re: 0.25, im: 0.0 },
+                C { re: 0.0, im: 0.0 },
+                C { re: 0.5, im: 0.0 },
+                C { re: 0.0, im: 0.0 },
+                C { re: -0.25, im: 0.0 },
+                C { re: 0.0, im: 0.0 },
+            ]
+        );
+        assert_eq!(format!("{:?}", reg), "QReg { 0: Complex { re: 0.25, im: 0.0 }, 1: Complex { re: 0.25, im: 0.0 }, 2: Complex { re: 0.25, im: 0.0 }, 3: Complex { re: 0.0, im: 0.0 }, 4: Complex { re: -0.25, im: 0.0 }, 5: Complex { re: -0.25, im: 0.0 }, 6: Complex { re: -0.25, im: 0.0 }, 7: Complex { re: 0.0, im: 0.0 }, .. }".to_string());
+
+        assert_eq!(reg.measure_mask(mask).get() & !mask, 0);
+    }
+
+    #[test]
+    fn display_options() {
+        let mut reg = QReg::new(3);
+        reg.apply(&op::h(0b111));
+        assert_eq!(format!("{:?}", reg).matches("Complex").count(), 8);
+
+        reg.set_display_options(4, 2);
+        assert_eq!(format!("{:?}", reg), "QReg { 0: Complex { re: 0.35, im: 0.00 }, 1: Complex { re: 0.35, im: 0.00 }, 2: Complex { re: 0.35, im: 0.00 }, 3: Complex { re: 0.35, im: 0.00 }, .. }");
+
+        reg.set_display_options(16, 1);
+        assert_eq!(format!("{:?}", reg).matches("re: 0.4").count(), 8);
+        assert!(!format!("{:?}", reg).contains(".."));
+
+        let reg = reg * QReg::new(2);
+        assert_eq!(format!("{:?}", reg).matches("Complex").count(), 16);
+    }
+
+    #[test]
+    fn tensor() {
+        const EPS: R = 10. * TOLERANCE;
+
+        let pend_ops = op::h(0b01);
+
+        let mut reg1 = QReg::with_state(2, 0b01);
+        let mut reg2 = QReg::with_state(1, 0b1);
+
+        reg1.apply(&pend_ops);
+        reg2.apply(&pend_ops);
+
+        let test_prob = (reg1 * reg2).get_probabilities();
+        let true_prob = vec![0.25, 0.25, 0., 0., 0.25, 0.25, 0., 0.];
+
+        assert!(test_prob
+            .into_iter()
+            .zip(true_prob.into_iter())
+            .all(|(a, b)| (a - b).abs() < EPS));
+
+        let mut reg3 = QReg::with_state(3, 0b101);
+        let pend_ops = op::h(0b101);
+
+        reg3.apply(&pend_ops);
+
+        let test_prob = reg3.get_probabilities();
+        let true_prob = vec![0.25, 0.25, 0., 0., 0.25, 0.25, 0., 0.];
+
+        assert!(test_prob
+            .into_iter()
+            .zip(true_prob.into_iter())
+            .all(|(a, b)| (a - b).abs() < EPS));
+    }
+
+    #[test]
+    fn tensor_prod_tracked() {
+        let mut reg1 = QReg::new(2);
+        let mut reg2 = QReg::new(3);
+        reg1.apply(&op::x(0b01));
+        reg2.apply(&op::x(0b010));
+
+        let (reg, offset) = reg1.clone().tensor_prod_tracked(reg2.clone());
+        assert_eq!(offset, reg1.num());
+        assert_eq!(reg.num(), 5);
+        assert_eq!(reg.get_probabilities(), (reg1 * reg2).get_probabilities());
+
+        let mut reg = reg;
+        assert_eq!(reg.measure_mask(0b010 << offset).get(), 0b010 << offset);
+    }
+
+    #[test]
+    fn histogram() {
+        let mut q = QReg::with_state(8, 123);
+
+        q.apply(&op::h(255));
+
+        for _ in 0..10 {
+            let hist = q.sample_all(2048);
+            assert_eq!(hist.len(), 256);
+            assert_eq!(hist.iter().sum::<usize>(), 2048);
+        }
+    }
+
+    #[test]
+    fn histogram_exact() {
+        const COUNT: N = 20_000;
+
+        let mut q = QReg::new(3);
+        q.apply(&(op::ry(0.6, 0b001) * op::ry(1.9, 0b010) * op::ry(2.8, 0b100)));
+        let p = q.get_probabilities();
+
+        for hist in [q.sample_all_exact(COUNT), q.sample_all(COUNT)] {
+            assert_eq!(hist.len(), 8);
+            assert_eq!(hist.iter().sum::<N>(), COUNT);
+
+            for (n, p) in hist.into_iter().zip(&p) {
+                let mean = COUNT as R * p;
+                let sigma = (mean * (1.0 - p)).sqrt();
+                assert!((n as R - mean).abs() <= 5.0 * sigma + 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn schmidt_rank() {
+        const TOL: R = 10. * TOLERANCE;
+
+        let mut q = QReg::new(2);
+        q.apply(&op::h(0b11));
+        assert_eq!(q.schmidt_rank(0b01, TOL), 1);
+        assert_eq!(q.schmidt_rank(0b00, TOL), 1);
+
+        q.apply(&op::x(0b10).c(0b01).unwrap());
+        assert_eq!(q.schmidt_rank(0b01, TOL), 1);
+
+        let mut q = QReg::new(2);
+        q.apply(&(op::h(0b01) * op::x(0b10).c(0b01).unwrap()));
+        assert_eq!(q.schmidt_rank(0b01, TOL), 2);
+        assert_eq!(q.schmidt_rank(0b10, TOL), 2);
+
+        let mut q = QReg::new(4);
+        q.apply(
+            &(op::h(0b0011)
+                * op::x(0b0100).c(0b0001).unwrap()
+                * op::rx(0.7, 0b1000).c(0b0010).unwrap()),
+        );
+        assert_eq!(q.schmidt_rank(0b0011, TOL), 4);
+        assert_eq!(q.schmidt_rank(0b0101, TOL), 1);
+        assert_eq!(q.schmidt_rank(0b0001, TOL), 2);
+        assert_eq!(q.schmidt_rank(0b0110, TOL), 4);
+    }
+
+    #[test]
+    fn bloch_vector() {
+        const EPS: R = 10. * TOLERANCE;
+        let assert_vector = |q: &QReg, idx: N, (x, y, z): (R, R, R)| {
+            let v = q.bloch_vector(idx);
+            assert!(
+                (v.0 - x).abs() < EPS && (v.1 - y).abs() < EPS && (v.2 - z).abs() < EPS,
+                "{:?}",
+                v
+            );
+        };
+
+        let mut q = QReg::new(3);
+        assert_vector(&q, 0, (0.0, 0.0, 1.0));
+
+        q.apply(&(op::x(0b001) * op::h(0b010) * op::h(0b100) * op::s(0b100)));
+        assert_vector(&q, 0, (0.0, 0.0, -1.0));
+        assert_vector(&q, 1, (1.0, 0.0, 0.0));
+        assert_vector(&q, 2, (0.0, 1.0, 0.0));
+
+        let mut q = QReg::new(2);
+        q.apply(&(op::h(0b01) * op::cnot(0b01, 0b10)));
+        assert_vector(&q, 0, (0.0, 0.0, 0.0));
+        assert_vector(&q, 1, (0.0, 0.0, 0.0));
+
+        let mut q = QReg::new(1);
+        q.apply(&op::ry(0.4, 0b1));
+        assert_vector(&q, 0, ((0.4 as R).sin(), 0.0, (0.4 as R).cos()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Qubit index is out of range!")]
+    fn bloch_vector_out_of_range() {
+        QReg::new(2).bloch_vector(2);
+    }
+
+    #[test]
+    fn approx_eq() {
+        const TOL: R = 10. * TOLERANCE;
+
+        let mut q1 = QReg::new(2);
+        q1.apply(&(op::h(0b01) * op::cnot(0b01, 0b10)));
+
+        // Z·X·Z·X = -I, so q2 gains a global phase of π
+        let mut q2 = QReg::new(2);
+        q2.apply(&(op::h(0b01) * op::x(0b01) * op::z(0b01) * op::x(0b01) * op::z(0b01)));
+        q2.apply(&op::cnot(0b01, 0b10));
+
+        assert_ne!(q1.get_polar(), q2.get_polar());
+        assert!(q1.approx_eq(&q2, TOL));
+        assert!(q2.approx_eq(&q1, TOL));
+        assert!(q1.approx_eq(&q1, 0.0));
+
+        let mut q3 = q1.clone();
+        q3.apply(&op::z(0b01));
+        assert!(!q1.approx_eq(&q3, TOL));
+
+        let mut q4 = q1.clone();
+        q4.apply(&op::ry(1e-3, 0b10));
+        assert!(!q1.approx_eq(&q4, TOL));
+        assert!(q1.approx_eq(&q4, 1e-2));
+
+        assert!(!q1.approx_eq(&QReg::new(3), TOL));
+        assert!(!q1.approx_eq(&QReg::with_state(2, 0b01), TOL));
+    }
+
+    #[test]
+    fn postselect() {
+        const EPS: R = 10. * TOLERANCE;
+
+        let mut q = QReg::new(3);
+        q.apply(&(op::h(0b001) * op::cnot(0b001, 0b010)));
+        let bell = q.clone();
+
+        assert_eq!(q.postselect(0b011, 0b001), None);
+        assert_eq!(q.get_probabilities(), bell.get_probabilities());
+
+        let prob = q.postselect(0b010, 0b110).unwrap();
+        assert!((prob - 0.5).abs() < EPS);
+        assert!((q.get_absolute() - 1.0).abs() < EPS);
+        assert_eq!(q.get_probabilities()[0b011], 1.0);
+
+        let mut q = bell;
+        assert!((q.postselect(0b100, 0).unwrap() - 1.0).abs() < EPS);
+        assert!((q.get_probabilities()[0b000] - 0.5).abs() < EPS);
+        assert!((q.get_probabilities()[0b011] - 0.5).abs() < EPS);
+    }
+
+    #[test]
+    fn tensor_many() {
+        let q = QReg::tensor_many(vec![QReg::with_state(1, 1); 3]);
+        assert_eq!(q.num(), 3);
+        assert_eq!(q.get_polar()[0b111], (1.0, 0.0));
+
+        let regs = vec![
+            QReg::with_state(2, 0b10),
+            QReg::with_state(1, 0b0),
+            QReg::with_state(3, 0b101),
+        ];
+        let q = QReg::tensor_many(regs.clone());
+        assert_eq!(q.num(), 6);
+        assert_eq!(q.get_probabilities()[0b101010], 1.0);
+        assert!(q.approx_eq(
+            &(regs[0].clone() * regs[1].clone() * regs[2].clone()),
+            TOLERANCE
+        ));
+
+        assert_eq!(QReg::tensor_many(vec![]).num(), 0);
+    }
+
+    #[test]
+    fn sample_expectation_z() {
+        let mut q = QReg::new(3);
+        q.apply(&(op::ry(1.2, 0b001) * op::ry(0.4, 0b010) * op::cnot(0b001, 0b100)));
+
+        for mask in [0b001, 0b011, 0b110] {
+            let exact = q.expectation_z(mask);
+
+            let (_, error_small) = q.sample_expectation_z(mask, 1_000);
+            let (mean, error) = q.sample_expectation_z(mask, 100_000);
+            assert!((mean - exact).abs() < 6. * error);
+            assert!((error * 10. - error_small).abs() < 0.2 * error_small);
+        }
+
+        // deterministic parity has no shot noise
+        assert_eq!(q.sample_expectation_z(0b101, 100), (1., 0.));
+    }
+
+    #[test]
+    fn measurement_entropy() {
+        const TOL: R = 10. * TOLERANCE;
+
+        for k in 0..5 {
+            let q = QReg::new(5).then(&op::h((1 << k) - 1));
+            assert!((q.measurement_entropy(0b11111) - k as R).abs() < TOL);
+            assert!((q.measurement_entropy(0b00011) - k.min(2) as R).abs() < TOL);
+        }
+
+        let q = QReg::with_state(4, 0b1010);
+        assert_eq!(q.probabilities_of(0b1100), [0., 0., 1., 0.]);
+        assert_eq!(q.measurement_entropy(0b1111), 0.);
+
+        let q = QReg::new(2).then(&(op::h(0b01) * op::cnot(0b01, 0b10)));
+        assert!((q.measurement_entropy(0b01) - 1.).abs() < TOL);
+        assert!((q.measurement_entropy(0b11) - 1.).abs() < TOL);
+        assert_eq!(q.measurement_entropy(0), 0.);
+    }
+
+    #[test]
+    fn endianness() {
+        let mut q = QReg::new(4);
+        q.apply(&(op::x(0b0011) * op::h(0b1000)));
+        assert_eq!(q.endianness(), Endianness::Little);
+
+        let little = q.get_probabilities();
+        assert_eq!(q.argmax().0, 0b0011);
+        assert_eq!(q.clone().measure().get() & 0b0111, 0b0011);
+
+        q.set_endianness(Endianness::Big);
+        let big = q.get_probabilities();
+        for idx in 0..16 {
+            assert_eq!(big[Endianness::Big.convert(idx, 4)], little[idx]);
+        }
+        assert_eq!(q.get_polar()[0b1100], q.get_polar()[0b1101]);
+        assert_eq!(q.argmax().0, 0b1100);
+        assert_eq!(q.clone().measure().get() & 0b1110, 0b1100);
+        assert_eq!(q.clone().measure_mask(0b0001).get(), 0b1000);
+        assert!(q
+            .run_shots(&op::id(), 10)
+            .into_iter()
+            .all(|c| c.get() & 0b1110 == 0b1100));
+        assert_eq!(
+            q.sample_all_exact(100)[0b1100..0b1110].iter().sum::<N>(),
+            100
+        );
+        assert_eq!(q.sample_expectation_z(0b0001, 100), (-1., 0.));
+        assert_eq!(q.probabilities_of(0b0001), [0., 1.]);
+
+        // endianness is kept in the tensor product of registers
+        let q = q * QReg::new(1);
+        assert_eq!(q.argmax().0, 0b11000);
+
+        assert_eq!(Endianness::Big.convert(0b1, 1), 0b1);
+        assert_eq!(Endianness::Big.convert(0, 0), 0);
+    }
+
+    #[test]
+    fn expectation_pauli() {
+        const TOL: R = 10. * TOLERANCE;
+
+        let mut q = QReg::new(3);
+        q.apply(&(op::ry(0.7, 0b001) * op::rx(1.3, 0b010) * op::cnot(0b010, 0b100)));
+
+        // single qubit observables agree with Bloch vector
+        let (x, y, z) = q.bloch_vector(0);
+        assert!((q.expectation_pauli(&PauliString::x(0b001)) - x).abs() < TOL);
+        assert!((q.expectation_pauli(&PauliString::y(0b001)) - y).abs() < TOL);
+        assert!((q.expectation_pauli(&PauliString::z(0b001)) - z).abs() < TOL);
+        let (x, y, z) = q.bloch_vector(1);
+        assert!((q.expectation_pauli(&PauliString::x(0b010)) - x).abs() < TOL);
+        assert!((q.expectation_pauli(&PauliString::y(0b010)) - y).abs() < TOL);
+        assert!((q.expectation_pauli(&PauliString::z(0b010)) - z).abs() < TOL);
+
+        assert!((q.expectation_pauli(&PauliString::z(0b101)) - q.expectation_z(0b101)).abs() < TOL);
+        assert_eq!(q.expectation_pauli(&PauliString::default()), 1.);
+
+        let observables = [
+            PauliString::x(0b111),
+            PauliString::y(0b110),
+            PauliString::new(0b001, 0b010, 0b100).unwrap(),
+            PauliString::new(0b100, 0b011, 0).unwrap(),
+            PauliString::z(0b110),
+        ];
+        let batch = q.expectation_batch(&observables);
+        for (obs, value) in observables.iter().zip(&batch) {
+            let single = q.expectation_pauli(obs);
+            assert!((single - value).abs() < TOL);
+
+            // compare with explicit basis change into Z basis
+            let (x_mask, z_mask) = obs.masks();
+            let y_mask = x_mask & z_mask;
+            let rotated = q.applied(&(op::s(y_mask).dgr() * op::h(x_mask)));
+            assert!((rotated.expectation_z(x_mask | z_mask) - value).abs() < TOL);
+        }
+
+        assert!(PauliString::new(0b01, 0b11, 0).is_none());
+    }
+
+    #[test]
+    fn argmax() {
+        let mut q = QReg::new(3);
+        q.apply(&(op::ry(0.8, 0b001) * op::ry(2.5, 0b100)));
+
+        let probs = q.get_probabilities();
+        let (idx, prob) = q.argmax();
+        assert_eq!(idx, 0b100);
+        assert!((prob - probs[0b100]).abs() < TOLERANCE);
+        assert!(probs.iter().all(|p| *p <= prob + TOLERANCE));
+
+        // ties are resolved in favour of the lowest state
+        let q = QReg::new(4).then(&op::h(0b1010));
+        assert_eq!(q.argmax().0, 0b0000);
+        let q = QReg::new(4).then(&(op::h(0b0110) * op::x(0b0001)));
+        assert_eq!(q.argmax().0, 0b0001);
+
+        #[cfg(feature = "multi-thread")]
+        if let Some(q) = QReg::new(12).num_threads(2) {
+            let q = q.then(&op::h(0b111111111111));
+            assert_eq!(q.argmax().0, 0);
+            let q = q.then(&op::ry(0.1, 0b100000000000));
+            assert_eq!(q.argmax().0, 0b100000000000);
+        }
+    }
+
+    #[test]
+    fn correlation() {
+        let mut q = QReg::new(3);
+        q.apply(&(op::h(0b001) * op::cnot(0b001, 0b010) * op::x(0b010)));
+
+        assert!((q.correlation(0b001, 0b010) + 1.).abs() < 10. * TOLERANCE);
+        assert!((q.correlation(0b001, 0b001) - 1.).abs() < 10. * TOLERANCE);
+        assert_eq!(q.correlation(0b001, 0b100), 0.);
+
+        q.apply(&op::h(0b100));
+        assert!(q.correlation(0b001, 0b100).abs() < 10. * TOLERANCE);
+        assert_eq!(q.correlation(0b011, 0b011), 0.);
+
+        let mut bell = QReg::new(2);
+        bell.apply(&(op::h(0b01) * op::cnot(0b01, 0b10)));
+        assert!((bell.correlation(0b01, 0b10) - 1.).abs() < 10. * TOLERANCE);
+    }
+
+    #[test]
+    fn prepare() {
+        let amps = [C::new(1., 0.), C::new(0., 2.), C_ZERO, C::new(-2., 0.)];
+        let q = QReg::prepare(2, &amps).unwrap();
+
+        let expected = [1. / 9., 4. / 9., 0., 4. / 9.];
+        q.get_probabilities()
+            .into_iter()
+            .zip(expected)
+            .for_each(|(p, e)| assert!((p - e).abs() < TOLERANCE));
+        assert!((q.get_absolute() - 1.).abs() < TOLERANCE);
+
+        assert!(QReg::prepare(1, &amps).is_none());
+        assert!(QReg::prepare(2, &[C_ZERO; 4]).is_none());
+    }
+
+    #[test]
+    fn from_creg() {
+        let q = QReg::from_creg(&CReg::with_state(4, 0b1010));
+        assert_eq!(q.num(), 4);
+        assert_eq!(q.get_polar()[0b1010], (1.0, 0.0));
+        assert_eq!(q.get_probabilities().iter().sum::<R>(), 1.0);
+
+        let q = QReg::from_creg(&CReg::new(0));
+        assert_eq!(q.get_probabilities(), [1.0]);
+    }
+
+    #[test]
+    fn expectation_z() {
+        const EPS: R = 10. * TOLERANCE;
+
+        let mut q = QReg::new(3);
+        q.apply(
+            &(op::ry(0.3, 0b001)
+                * op::ry(1.1, 0b010)
+                * op::ry(2.5, 0b100)
+                * op::cnot(0b001, 0b100)),
+        );
+
+        let probs = q.get_probabilities();
+        for mask in 0..8 {
+            let expected = probs
+                .iter()
+                .enumerate()
+                .map(|(idx, p)| {
+                    if (idx & mask).count_ones() & 1 == 1 {
+                        -p
+                    } else {
+                        *p
+                    }
+                })
+                .sum::<R>();
+            assert!((q.expectation_z(mask) - expected).abs() < EPS);
+
+            #[cfg(feature = "multi-thread")]
+            if let Some(q) = q.clone().num_threads(2) {
+                assert!((q.expectation_z(mask) - expected).abs() < EPS);
+            }
+        }
+
+        assert_eq!(q.expectation_z(0), 1.0);
+        assert!((q.expectation_z(0b010) - (1.1 as R).cos()).abs() < EPS);
+    }
+
+    #[test]
+    fn expectation_observable() {
+        const EPS: R = 10. * TOLERANCE;
+        let c = |re: R, im: R| C { re, im };
+        let pauli_x = [C_ZERO, C_ONE, C_ONE, C_ZERO];
+        let pauli_y = [C_ZERO, c(0.0, -1.0), c(0.0, 1.0), C_ZERO];
+        let pauli_z = [C_ONE, C_ZERO, C_ZERO, -C_ONE];
+
+        let mut q = QReg::new(3);
+        q.apply(&(op::h(0b111) * op::s(0b010) * op::ry(0.7, 0b100) * op::cnot(0b001, 0b100)));
+
+        for idx in 0..3 {
+            let (x, y, z) = q.bloch_vector(idx);
+            assert!((q.expectation_observable(idx, pauli_x).unwrap() - x).abs() < EPS);
+            assert!((q.expectation_observable(idx, pauli_y).unwrap() - y).abs() < EPS);
+            assert!((q.expectation_observable(idx, pauli_z).unwrap() - z).abs() < EPS);
+        }
+
+        let obs = [c(2.0, 0.0), c(0.5, -1.0), c(0.5, 1.0), c(-3.0, 0.0)];
+        let (x, y, z) = q.bloch_vector(1);
+        let expected = 0.5 * (2.0 - 3.0) + 0.5 * x + 1.0 * y + 0.5 * (2.0 + 3.0) * z;
+        assert!((q.expectation_observable(1, obs).unwrap() - expected).abs() < EPS);
+
+        assert_eq!(
+            q.expectation_observable(0, [C_ZERO, C_ONE, C_ZERO, C_ZERO]),
+            None
+        );
+    }
+
+    #[test]
+    fn truncate() {
+        let mut q = QReg::new(2);
+        q.apply(&(op::ry(1.0, 0b01) * op::ry(0.02, 0b10)));
+        let prob = q.get_probabilities();
+        assert!(prob[0b10] > 0.0 && prob[0b11] > 0.0);
+
+        q.truncate(1e-3);
+        let truncated = q.get_probabilities();
+        let norm = prob[0b00] + prob[0b01];
+        assert_eq!(truncated[0b10], 0.0);
+        assert_eq!(truncated[0b11], 0.0);
+        assert!((truncated[0b00] - prob[0b00] / norm).abs() < TOLERANCE);
+        assert!((truncated[0b01] - prob[0b01] / norm).abs() < TOLERANCE);
+        assert!((q.get_absolute() - 1.0).abs() < TOLERANCE);
+    }
+
+    #[test]
+    fn then() {
+        let mut q = QReg::new(3);
+        q.apply(&op::h(0b001));
+        q.apply(&op::x(0b010).c(0b001).unwrap());
+        q.apply(&op::rx(0.3, 0b100).c(0b011).unwrap());
+
+        let q_then = QReg::new(3)
+            .then(&op::h(0b001))
+            .then(&op::x(0b010).c(0b001).unwrap())
+            .then(&op::rx(0.3, 0b100).c(0b011).unwrap()[0]);
+
+        assert_eq!(q.get_polar(), q_then.get_polar());
+    }
+
+    #[test]
+    fn applied() {
+        let q = QReg::new(2).then(&op::h(0b01));
+        let before = q.get_polar();
+
+        let q_applied = q.applied(&op::x(0b10).c(0b01).unwrap());
+        assert_eq!(q.get_polar(), before);
+        assert_eq!(
+            q_applied.get_polar(),
+            q.clone().then(&op::cnot(0b01, 0b10)).get_polar()
+        );
+        assert_ne!(q_applied.get_polar(), before);
+    }
+
+    #[test]
+    fn apply_oracle() {
+        let mut q = QReg::new(3);
+        q.apply(&(op::h(0b001) * op::ry(0.7, 0b100)));
+        let mut q_op = q.clone();
+
+        q.apply_oracle(|idx| idx ^ 0b010);
+        q_op.apply(&op::x(0b010));
+        assert_eq!(q.get_polar(), q_op.get_polar());
+
+        q.apply_oracle(|idx| if idx & 0b001 != 0 { idx ^ 0b100 } else { idx });
+        q_op.apply(&op::cnot(0b001, 0b100));
+        assert_eq!(q.get_polar(), q_op.get_polar());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Oracle should be a bijection!")]
+    fn apply_oracle_not_bijection() {
+        QReg::new(2).apply_oracle(|idx| idx & 0b01);
+    }
+
+    #[test]
+    #[should_panic(expected = "Oracle maps state out of register!")]
+    fn apply_oracle_out_of_range() {
+        QReg::new(2).apply_oracle(|idx| idx + 1);
+    }
+
+    #[test]
+    fn apply_phase_oracle() {
+        let mut q = QReg::new(3);
+        q.apply(&op::h(0b111));
+        let before = q.get_polar();
+
+        q.apply_phase_oracle(|idx| idx == 0b101);
+        q.get_polar()
+            .into_iter()
+            .zip(before)
+            .enumerate()
+            .for_each(|(idx, (after, before))| {
+                assert_eq!(after.0, before.0);
+                if idx == 0b101 {
+                    assert!(((after.1 - before.1).abs() - PI).abs() < 10. * TOLERANCE);
+                } else {
+                    assert_eq!(after.1, before.1);
+                }
+            });
+    }
+
+    #[test]
+    fn apply_iter() {
+        let circuit = op::bench_circuit();
+
+        let mut q = QReg::new(3);
+        q.apply(&circuit);
+
+        let mut q_iter = QReg::new(3);
+        q_iter.apply_iter(circuit.iter().cloned());
+
+        assert_eq!(q.get_polar(), q_iter.get_polar());
+
+        #[cfg(feature = "multi-thread")]
+        if let Some(mut q_iter) = QReg::new(3).num_threads(2) {
+            q_iter.apply_iter(circuit.iter().cloned());
+
+            assert_eq!(q.get_probabilities(), q_iter.get_probabilities());
+        }
+    }
+
+    #[test]
+    fn run_shots() {
+        const SHOTS: usize = 4096;
+
+        let q = QReg::new(3);
+        let bell = op::h(0b01) * op::x(0b10).c(0b01).unwrap();
+
+        let shots = q.run_shots(&bell, SHOTS);
+        assert_eq!(shots.len(), SHOTS);
+        assert_eq!(q.get_probabilities()[0], 1.0);
+
+        let mut hist = [0; 8];
+        shots.iter().for_each(|c| hist[c.get()] += 1);
+        assert_eq!(hist[0b00] + hist[0b11], SHOTS);
+
+        let mut q = q;
+        q.apply(&bell);
+        let expected = q.sample_all(SHOTS);
+        for idx in [0b00, 0b11] {
+            assert!((hist[idx] as R - expected[idx] as R).abs() < 0.1 * SHOTS as R);
         }
+    }
 
-        let rand_idx =
-            thread_rng().sample(rand_distr::WeightedIndex::new(self.get_probabilities()).unwrap());
+    #[test]
+    fn try_apply() {
+        let mut q = QReg::new(3);
+        assert_eq!(q.try_apply(&(op::h(0b001) * op::x(0b10000))), Err(0b10000));
+        assert_eq!(q.get_probabilities()[0], 1.0);
 
-        self.collapse_mask(rand_idx, mask);
-        super::CReg::with_state(self.q_num, rand_idx & mask)
+        assert_eq!(q.try_apply(&op::x(0b101)), Ok(()));
+        assert_eq!(q.get_probabilities()[0b101], 1.0);
     }
 
-    /// Measure all qubits into classical register.
-    /// Wavefunction of quantum register will collapse after measurement.
-    pub fn measure(&mut self) -> super::CReg {
-        self.measure_mask(self.q_mask)
+    #[test]
+    fn apply_barrier() {
+        let mut q = QReg::new(3);
+        q.apply(&op::h(0b011));
+        let psi = q.psi.as_ptr();
+
+        q.apply(&op::barrier(0b111));
+        q.apply(&(op::id() * op::barrier(0b001) * op::barrier(0b110)));
+        q.apply_iter(op::barrier(0b111).iter().cloned());
+        assert_eq!(q.psi.as_ptr(), psi);
+
+        let mut expected = QReg::new(3);
+        expected.apply(&(op::h(0b011) * op::x(0b100)));
+        q.apply(&(op::barrier(0b111) * op::x(0b100) * op::barrier(0b111)));
+        assert_eq!(q.get_amplitudes(), expected.get_amplitudes());
     }
 
-    /// Make a histogram for quantum register.
-    /// This histogram also could be obtained by calling [`measure`](Reg::measure) *count* times.
-    /// But [`sample_all`](Reg::sample_all) does not collapse wavefunction and executes __MUSH FASTER__.
-    /// If you want to simulate the execution of quantum computer, you would prefer [`sample_all`](Reg::sample_all).
-    pub fn sample_all(&self, count: N) -> Vec<N> {
-        use std::cmp::Ordering;
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "out of 3-qubit register")]
+    fn apply_out_of_range() {
+        QReg::new(3).apply(&op::h(0b1000));
+    }
 
-        let p = self.get_probabilities();
-        let c = count as R;
-        let c_sqrt = c.sqrt();
+    #[test]
+    fn probability_histogram() {
+        let mut q = QReg::new(4);
+        q.apply(&(op::x(0b0010) * op::h(0b1000)));
+
+        let hist = q.probability_histogram(0b1110, false);
+        assert_eq!(hist.len(), 8);
+        for (idx, (label, p)) in hist.into_iter().enumerate() {
+            assert_eq!(label.parse::<CReg>().unwrap().get(), idx);
+            assert_eq!(
+                p,
+                if idx == 0b001 || idx == 0b101 {
+                    0.5
+                } else {
+                    0.0
+                }
+            );
+        }
 
-        let (mut n, delta) = match self.th {
-            threading::Single => {
-                let mut rng = rand::thread_rng();
-                let n = p
-                    .iter()
-                    .map(|&p| {
-                        let rnd: R = rng.sample(rand_distr::StandardNormal);
-                        p.sqrt() * rnd
-                    })
-                    .collect::<Vec<R>>();
+        let hist = q.probability_histogram(0b1110, true);
+        assert_eq!(
+            hist,
+            [("(001)".to_string(), 0.5), ("(101)".to_string(), 0.5)]
+        );
 
-                let n_sum = n.iter().sum::<R>();
+        q.set_endianness(Endianness::Big);
+        let hist = q.probability_histogram(0b1110, true);
+        assert_eq!(
+            hist,
+            [("(100)".to_string(), 0.5), ("(101)".to_string(), 0.5)]
+        );
+        let hist = q.probability_histogram(0b1110, false);
+        assert_eq!(hist.len(), 8);
+        assert_eq!(hist[0b100], ("(100)".to_string(), 0.5));
+    }
 
-                let n = (0..self.psi.len())
-                    .map(|idx| {
-                        ((c * p[idx] + c_sqrt * (n[idx] - n_sum * p[idx])).round() as Z).max(0) as N
-                    })
-                    .collect::<Vec<N>>();
+    #[test]
+    fn num_threads_or_single() {
+        let circuit = op::qft(0b1111) * op::h(0b0011);
+        let mut expected = QReg::new(4);
+        expected.apply(&circuit);
+
+        for num_threads in [0, 1, 2, usize::MAX] {
+            let mut q = QReg::new(4).num_threads_or_single(num_threads);
+            q.apply(&circuit);
+            assert!(q.approx_eq(&expected, TOLERANCE));
+        }
 
-                let delta = n.iter().sum::<N>() as Z - count as Z;
+        // thread pool with unreasonable stack size could not be created
+        #[cfg(feature = "multi-thread")]
+        {
+            let builder = rayon::ThreadPoolBuilder::new().stack_size(1 << 60);
+            let mut q = QReg::new(4).num_threads_or_single_with(2, builder);
+            assert!(matches!(q.th, super::threading::Single));
+            q.apply(&circuit);
+            assert!(q.approx_eq(&expected, TOLERANCE));
+        }
+    }
 
-                (n, delta)
-            }
-            #[cfg(feature = "multi-thread")]
-            threading::Multi(n) => crate::threads::global_install(n, || {
-                let n = p
-                    .par_iter()
-                    .map(|&p| {
-                        let rnd: R = rand::thread_rng().sample(rand_distr::StandardNormal);
-                        p.sqrt() * rnd
-                    })
-                    .collect::<Vec<R>>();
+    #[test]
+    fn too_many_qubits() {
+        let message = std::panic::catch_unwind(|| QReg::new(40))
+            .unwrap_err()
+            .downcast::<String>()
+            .unwrap();
+        // 2^40 amplitudes in two buffers
+        let gib = 1024 * 2 * std::mem::size_of::<C>();
+        assert_eq!(
+            *message,
+            format!(
+                "Requested 40 qubits exceeds limit 30 (would require {} GiB)",
+                gib
+            )
+        );
+    }
 
-                let n_sum = n.par_iter().sum::<R>();
+    #[test]
+    #[should_panic(expected = "Requested 64 qubits exceeds limit 30")]
+    fn too_many_qubits_prepare() {
+        QReg::prepare(64, &[C_ONE]);
+    }
 
-                let n = (0..self.psi.len())
-                    .map(|idx| {
-                        ((c * p[idx] + c_sqrt * (n[idx] - n_sum * p[idx])).round() as Z).max(0) as N
-                    })
-                    .collect::<Vec<N>>();
+    #[test]
+    #[should_panic(expected = "Requested 64 qubits exceeds limit 30")]
+    fn too_many_qubits_superposition() {
+        QReg::with_superposition(64, &[0]);
+    }
 
-                let delta = n.par_iter().sum::<N>() as Z - count as Z;
+    #[test]
+    fn max_qubits() {
+        // limit is changed only in doctests, which run in separate processes
+        assert_eq!(super::max_qubits(), MAX_QUBITS);
+        assert_eq!(set_max_qubits(N::BITS as N), None);
+        assert_eq!(set_max_qubits(MAX_QUBITS), Some(MAX_QUBITS));
+    }
 
-                (n, delta)
-            }),
-        };
-        match delta.cmp(&0) {
-            Ordering::Less => {
-                let delta = delta.unsigned_abs();
-                let delta = (delta >> self.q_num, delta % self.q_mask);
-                for (idx, n) in n.iter_mut().enumerate() {
-                    *n += delta.0;
-                    if idx < delta.1 {
-                        *n += 1;
-                    }
-                }
-            }
-            Ordering::Greater => {
-                let mut delta = delta as N;
-                for idx in 0.. {
-                    if delta == 0 {
-                        break;
-                    }
-                    if n[idx & self.q_mask] == 0 {
-                        continue;
-                    }
-                    n[idx & self.q_mask] -= 1;
-                    delta -= 1;
-                }
-            }
-            _ => {}
-        }
+    #[test]
+    fn try_tensor_prod() {
+        let q = QReg::with_state(2, 1).try_tensor_prod(QReg::with_state(3, 1));
+        assert_eq!(q.unwrap().get_probabilities()[0b00101], 1.0);
 
-        n
+        assert!(QReg::new(16).try_tensor_prod(QReg::new(15)).is_none());
     }
-}
 
-impl Default for Reg {
-    fn default() -> Self {
-        Self::new(0)
+    #[test]
+    #[should_panic(expected = "Requested 32 qubits exceeds limit 30")]
+    fn tensor_prod_too_many_qubits() {
+        let _ = QReg::new(16) * QReg::new(16);
     }
-}
 
-impl fmt::Debug for Reg {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if 1 << self.q_num <= MAX_LEN_TO_DISPLAY {
-            self.psi[..(1 << self.q_num)]
-                .iter()
-                .enumerate()
-                .fold(&mut f.debug_struct("QReg"), |f, (idx, psi)| {
-                    f.field(&format!("{}", idx), psi)
-                })
-                .finish()
-        } else {
-            self.psi[..MAX_LEN_TO_DISPLAY]
-                .iter()
-                .enumerate()
-                .fold(&mut f.debug_struct("QReg"), |f, (idx, psi)| {
-                    f.field(&format!("{}", idx), psi)
-                })
-                .finish_non_exhaustive()
+    #[test]
+    fn apply_layer() {
+        let layers = [
+            op::h(0b00101) * op::x(0b10000).c(0b01000).unwrap() * op::rx(0.7, 0b00010),
+            op::rzz(1.1, 0b00011) * op::sqrt_swap(0b01100) * op::t(0b10000),
+            op::swap(0b10001) * op::y(0b00110),
+            op::h(0b11111),
+        ];
+
+        let layers = layers.map(|layer| layer.iter().cloned().collect::<Vec<_>>());
+
+        let mut q = QReg::new(5);
+        let mut q_layer = QReg::new(5);
+        for layer in &layers {
+            q.apply(layer);
+            q_layer.apply_layer(layer);
+            assert!(q.approx_eq(&q_layer, TOLERANCE));
+        }
+
+        q_layer.apply_layer(&[]);
+        assert!(q.approx_eq(&q_layer, TOLERANCE));
+
+        #[cfg(feature = "multi-thread")]
+        if let Some(mut q_sync) = QReg::new(5).num_threads(2) {
+            layers.iter().for_each(|layer| q_sync.apply_layer(layer));
+            assert!(q.approx_eq(&q_sync, TOLERANCE));
         }
     }
-}
 
-impl Mul for Reg {
-    type Output = Self;
-    fn mul(self, other: Self) -> Self {
-        self.tensor_prod(other)
+    #[test]
+    #[should_panic(expected = "Gates of layer should act on disjoint qubits!")]
+    fn apply_layer_overlapping() {
+        let layer = op::h(0b011) * op::x(0b100).c(0b010).unwrap();
+        QReg::new(3).apply_layer(&layer.iter().cloned().collect::<Vec<_>>());
     }
-}
 
-impl MulAssign for Reg {
-    fn mul_assign(&mut self, rhs: Self) {
-        *self = std::mem::take(self).tensor_prod(rhs);
+    #[test]
+    fn alias() {
+        let mut q = QReg::new(6);
+        q.set_alias("xyxyzxw");
+
+        assert_eq!(q.get_vreg_by_char('x').unwrap()[..], 0b100101);
+        assert_eq!(q.get_vreg_by_char('y').unwrap()[..], 0b001010);
+        assert_eq!(q.get_vreg_by_char('x').unwrap()[1], 0b000100);
+        assert!(q.get_vreg_by_char('w').is_none());
+        assert!(q.get_vreg_by_char('a').is_none());
+
+        let mut p = QReg::new(2);
+        p.set_alias("aa");
+        let q = QReg::new(3) * p;
+        assert_eq!(q.get_vreg_by_char('a').unwrap()[..], 0b11000);
+
+        let mut q = q;
+        q.set_alias("");
+        assert!(q.get_vreg_by_char('a').is_none());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::{math::types::*, prelude::*};
+    #[test]
+    fn reduced_density_matrix() {
+        let mut q = QReg::new(3);
+        q.apply(&(op::h(0b001) * op::ry(0.8, 0b100) * op::cnot(0b001, 0b010) * op::s(0b010)));
+
+        for mask in [0b001, 0b010, 0b101, 0b111, 0] {
+            let (dim, flat) = q.reduced_density_matrix_flat(mask);
+            let nested = q.reduced_density_matrix_of(mask);
+            assert_eq!(dim, 1 << (mask as N).count_ones());
+            assert_eq!(nested.len(), dim);
+            for (row, nested_row) in nested.iter().enumerate() {
+                assert_eq!(nested_row[..], flat[row * dim..(row + 1) * dim]);
+            }
+
+            // Hermitian with unit trace and diagonal of marginal probabilities
+            let probs = q.probabilities_of(mask);
+            for (row, nested_row) in nested.iter().enumerate() {
+                assert!((nested_row[row].re - probs[row]).abs() < TOLERANCE);
+                for (col, z) in nested_row.iter().enumerate() {
+                    assert!((z - nested[col][row].conj()).norm() < TOLERANCE);
+                }
+            }
+        }
+
+        let rho = q.reduced_density_matrix_of(0b101);
+        let rho_01 = q.reduced_density_matrix_of(0b001);
+        let (x, y, _) = q.bloch_vector(0);
+        assert!((rho_01[0][1] - C::new(x, -y) * 0.5).norm() < TOLERANCE);
+        assert!(rho[0b01][0b00].norm() < TOLERANCE);
+    }
 
     #[test]
-    fn quantum_reg() {
-        let mut reg = QReg::with_state(4, 0b1100);
-        let mask = 0b0110;
+    fn apply_index_phase() {
+        let mut q = QReg::new(4);
+        q.apply(&(op::h(0b1111) * op::ry(0.4, 0b0010)));
+        let before = q.get_polar();
+
+        // constant phase function is a global phase
+        let mut q_global = q.clone();
+        q_global.apply_index_phase(|_| 0.8);
+        q_global
+            .get_polar()
+            .into_iter()
+            .zip(&before)
+            .for_each(|((r1, phi1), (r2, phi2))| {
+                assert!(
+                    (C::from_polar(r1, phi1) - C::from_polar(*r2, phi2 + 0.8)).norm() < TOLERANCE
+                );
+            });
+
+        q.apply_index_phase(|idx| 0.1 * idx as R);
+        q.get_polar().into_iter().zip(&before).enumerate().for_each(
+            |(idx, ((r1, phi1), (r2, phi2)))| {
+                let expected = C::from_polar(*r2, phi2 + 0.1 * idx as R);
+                assert!((C::from_polar(r1, phi1) - expected).norm() < TOLERANCE);
+            },
+        );
 
-        let operator =
-            op::h(0b1111) * op::h(0b0011).c(0b1000).unwrap() * op::swap(0b1001).c(0b0010).unwrap();
+        let mut q_oracle = QReg::new(3);
+        q_oracle.apply(&op::h(0b111));
+        let mut q_phase = q_oracle.clone();
+        q_oracle.apply_phase_oracle(|idx| idx % 3 == 0);
+        q_phase.apply_index_phase(|idx| if idx % 3 == 0 { PI } else { 0. });
+        assert!(q_oracle.approx_eq(&q_phase, TOLERANCE));
+    }
 
-        reg.apply(&operator);
+    #[test]
+    fn with_superposition() {
+        let states = [0b0000, 0b0101, 0b1111, 0b0101, 0b1001];
+        let q = QReg::with_superposition(4, &states).unwrap();
 
-        assert_eq!(format!("{:?}", operator), "[H3, H12, C8_H3, C2_SWAP9]");
+        q.get_probabilities()
+            .into_iter()
+            .enumerate()
+            .for_each(|(idx, p)| {
+                let expected = if states.contains(&idx) { 0.25 } else { 0. };
+                assert!((p - expected).abs() < TOLERANCE);
+            });
+
+        assert!(QReg::with_superposition(4, &[]).is_none());
+        assert!(QReg::with_superposition(4, &[0b0001, 0b10000]).is_none());
         assert_eq!(
-            reg.psi,
-            [
-                C { re: 0.25, im: 0.0 },
-                C { re: 0.25, im: 0.0 },
-                C { re: 0.25, im: 0.0 },
-                C { re: 0.0, im: 0.0 },
-                C { re: -0.25, im: 0.0 },
-                C { re: -0.25, im: 0.0 },
-                C { re: -0.25, im: 0.0 },
-                C { re: 0.0, im: 0.0 },
-                C { re: -0.5, im: 0.0 },
-                C { re: 0.0, im: 0.0 },
-                C { re: 0.25, im: 0.0 },
-                C { re: 0.0, im: 0.0 },
-                C { re: 0.5, im: 0.0 },
-                C { re: 0.0, im: 0.0 },
-                C { re: -0.25, im: 0.0 },
-                C { re: 0.0, im: 0.0 },
-            ]
+            QReg::with_superposition(1, &[1]).unwrap().get_polar(),
+            QReg::with_state(1, 1).get_polar()
         );
-        assert_eq!(format!("{:?}", reg), "QReg { 0: Complex { re: 0.25, im: 0.0 }, 1: Complex { re: 0.25, im: 0.0 }, 2: Complex { re: 0.25, im: 0.0 }, 3: Complex { re: 0.0, im: 0.0 }, 4: Complex { re: -0.25, im: 0.0 }, 5: Complex { re: -0.25, im: 0.0 }, 6: Complex { re: -0.25, im: 0.0 }, 7: Complex { re: 0.0, im: 0.0 }, .. }".to_string());
-
-        assert_eq!(reg.measure_mask(mask).get() & !mask, 0);
     }
 
     #[test]
-    fn tensor() {
-        const EPS: f64 = 1e-9;
+    fn apply_into() {
+        let circuit = op::qft(0b1111) * op::h(0b0101) * op::x(0b1000).c(0b0010).unwrap();
 
-        let pend_ops = op::h(0b01);
+        let mut q = QReg::with_state(4, 0b0110);
+        let mut out = QReg::new(4);
 
-        let mut reg1 = QReg::with_state(2, 0b01);
-        let mut reg2 = QReg::with_state(1, 0b1);
+        q.apply_into(&circuit, &mut out);
+        assert_eq!(q.get_probabilities()[0b0110], 1.0);
 
-        reg1.apply(&pend_ops);
-        reg2.apply(&pend_ops);
+        q.apply(&circuit);
+        assert_eq!(q.get_polar(), out.get_polar());
 
-        let test_prob = (reg1 * reg2).get_probabilities();
-        let true_prob = vec![0.25, 0.25, 0., 0., 0.25, 0.25, 0., 0.];
+        // buffer of output register is reused for subsequent application
+        q.apply_into(&circuit, &mut out);
+        q.apply(&circuit);
+        assert_eq!(q.get_polar(), out.get_polar());
 
-        assert!(test_prob
-            .into_iter()
-            .zip(true_prob.into_iter())
-            .all(|(a, b)| (a - b).abs() < EPS));
+        #[cfg(feature = "multi-thread")]
+        if let Some(q_sync) = QReg::with_state(4, 0b0110).num_threads(2) {
+            let mut out = QReg::new(4);
+            q_sync.apply_into(&circuit, &mut out);
+
+            let mut q = QReg::with_state(4, 0b0110);
+            q.apply(&circuit);
+            for ((r1, phi1), (r2, phi2)) in q.get_polar().into_iter().zip(out.get_polar()) {
+                assert!((C::from_polar(r1, phi1) - C::from_polar(r2, phi2)).norm() < TOLERANCE);
+            }
+        }
+    }
 
-        let mut reg3 = QReg::with_state(3, 0b101);
-        let pend_ops = op::h(0b101);
+    #[test]
+    fn history() {
+        let mut q = QReg::new(3);
+        q.apply(&op::h(0b111));
+        q.measure_mask(0b001);
+        assert!(q.history().is_empty());
+
+        q.enable_history();
+        let outcomes = vec![
+            (0b010, q.measure_mask(0b010)),
+            (0b000, q.measure_mask(0b1000)),
+            (0b111, q.measure()),
+        ];
+        assert_eq!(q.history(), outcomes.as_slice());
+
+        // qubit collapsed by the first measurement keeps its value
+        assert_eq!(outcomes[2].1.get() & 0b010, outcomes[0].1.get());
+
+        let (q, _) = q.tensor_prod_tracked(QReg::new(1));
+        assert_eq!(q.history().len(), 3);
+    }
 
-        reg3.apply(&pend_ops);
+    #[test]
+    fn pauli_frame() {
+        let gates = [
+            op::x(0b0001),
+            op::y(0b0110),
+            op::z(0b1010),
+            op::x(0b1111),
+            op::y(0b1000),
+            op::h(0b0011),
+            op::h(0b1000),
+            op::s(0b0101),
+            op::s(0b1010).dgr(),
+            op::cnot(0b0001, 0b0100),
+            op::cnot(0b1000, 0b0011),
+            op::z(0b0110).c(0b0001).unwrap(),
+            op::swap(0b1001),
+            op::t(0b0010),
+            op::rz(0.4, 0b1000).c(0b0001).unwrap(),
+            op::rx(1.1, 0b0100),
+            op::x(0b0100).c(0b1001).unwrap(),
+        ];
+
+        // global phase of frame is tracked as well
+        let same = |a: &QReg, b: &QReg| {
+            a.get_amplitudes()
+                .iter()
+                .zip(b.get_amplitudes())
+                .all(|(a, b)| (a - b).norm() < TOLERANCE)
+        };
 
-        let test_prob = reg3.get_probabilities();
-        let true_prob = vec![0.25, 0.25, 0., 0., 0.25, 0.25, 0., 0.];
+        let mut seed = 7_usize;
+        for _ in 0..100 {
+            let circuit = (0..12).fold(op::h(0b1111), |circuit, _| {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                circuit * gates[(seed >> 33) % gates.len()].clone()
+            });
+
+            let mut q = QReg::with_state(4, 0b0110);
+            let expected = q.applied(&circuit);
+            q.enable_pauli_frame();
+            q.apply(&circuit);
+            assert!(same(&q, &expected), "{:?}", circuit);
+
+            let mut q_iter = QReg::with_state(4, 0b0110);
+            q_iter.enable_pauli_frame();
+            q_iter.apply_iter(circuit.iter().cloned());
+            assert!(same(&q_iter, &expected));
 
-        assert!(test_prob
-            .into_iter()
-            .zip(true_prob.into_iter())
-            .all(|(a, b)| (a - b).abs() < EPS));
+            #[cfg(feature = "multi-thread")]
+            if let Some(mut q_sync) = QReg::with_state(4, 0b0110).num_threads(2) {
+                q_sync.enable_pauli_frame();
+                q_sync.apply(&circuit);
+                assert!(same(&q_sync, &expected));
+            }
+        }
+
+        // teleportation with Pauli corrections on the last qubit
+        let bell = op::h(0b010) * op::cnot(0b010, 0b100);
+        let prepare = op::ry(1.3, 0b001) * op::rz(0.4, 0b001);
+        for c in 0..4 {
+            let mut q = QReg::new(3);
+            q.enable_pauli_frame();
+            q.apply(&(prepare.clone() * bell.clone() * op::cnot(0b001, 0b010) * op::h(0b001)));
+            q.postselect(0b011, c).unwrap();
+
+            let mut correction = op::id();
+            if c & 0b010 != 0 {
+                correction *= op::x(0b100);
+            }
+            if c & 0b001 != 0 {
+                correction *= op::z(0b100);
+            }
+            q.apply(&(correction * op::rz(-0.4, 0b100) * op::ry(-1.3, 0b100)));
+            assert_eq!(q.measure_mask(0b100).get(), 0);
+        }
     }
 
     #[test]
-    fn histogram() {
-        let mut q = QReg::with_state(8, 123);
+    fn amplitude_encode() {
+        let data = [0.5, -1.0, 2.0, 0.0, 1.5];
+        let norm = data.iter().map(|x| x * x).sum::<R>();
 
-        q.apply(&op::h(255));
+        let q = QReg::amplitude_encode(&data).unwrap();
+        assert_eq!(q.num(), 3);
 
-        for _ in 0..10 {
-            let hist = q.sample_all(2048);
-            assert_eq!(hist.len(), 256);
-            assert_eq!(hist.iter().sum::<usize>(), 2048);
+        let probs = q.get_probabilities();
+        for (idx, prob) in probs.into_iter().enumerate() {
+            let x = data.get(idx).copied().unwrap_or_default();
+            assert!((prob - x * x / norm).abs() < TOLERANCE);
+        }
+        assert!((q.get_amplitudes()[0b001].re + 1.0 / norm.sqrt()).abs() < TOLERANCE);
+
+        assert_eq!(QReg::amplitude_encode(&[-2.0]).unwrap().num(), 0);
+        assert!(QReg::amplitude_encode(&[0.0; 4]).is_none());
+        assert!(QReg::amplitude_encode(&[]).is_none());
+    }
+
+    #[test]
+    fn measure_mask_with_prob() {
+        let circuit =
+            op::ry(1.1, 0b001) * op::h(0b010) * op::cnot(0b001, 0b100) * op::rx(0.7, 0b100);
+        let mut q = QReg::new(3);
+        q.apply(&circuit);
+        let marginal = q.probabilities_of(0b101);
+
+        for _ in 0..20 {
+            let mut q = q.clone();
+            let (c, prob) = q.measure_mask_with_prob(0b101);
+            assert!((prob - marginal[super::compress(c.get(), 0b101)]).abs() < TOLERANCE);
+            assert!(prob > 0.);
+            assert!(
+                (q.probabilities_of(0b101)[super::compress(c.get(), 0b101)] - 1.).abs() < TOLERANCE
+            );
         }
+
+        let (c, prob) = q.measure_mask_with_prob(0b1000);
+        assert_eq!((c.get(), prob), (0, 1.));
     }
 }
@@ -94,6 +94,42 @@ impl Reg {
         let bi = bits_iter::BitsIter::from(mask);
         super::VReg(Ptr::new(0.into()), bi.collect())
     }
+
+    fn mask(&self) -> N {
+        self.1.iter().fold(0, |acc, idx| acc | idx)
+    }
+
+    /// Create virtual register, which contains qubits of both registers.
+    /// Qubits are ordered in the same way as in original quantum register.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let v = VReg::new(8);
+    /// // all even qubits plus qubit 3
+    /// let u = VReg::from(v[|i| i % 2 == 0]).union(&VReg::from(v[3]));
+    ///
+    /// assert_eq!(u[..], 0b01011101);
+    /// assert_eq!(u[2], v[3]);
+    /// ```
+    pub fn union(&self, other: &Self) -> Self {
+        Self::new_with_mask(self.mask() | other.mask())
+    }
+
+    /// Create virtual register, which contains only qubits presented in both registers.
+    /// Qubits are ordered in the same way as in original quantum register.
+    ///
+    /// ```rust
+    /// # use qvnt::prelude::*;
+    /// let v = VReg::new(8);
+    /// // even qubits among the lower half
+    /// let i = VReg::from(v[|i| i % 2 == 0]).intersect(&VReg::from(v[[0, 1, 2, 3]]));
+    ///
+    /// assert_eq!(i[..], 0b00000101);
+    /// assert_eq!(i[1], v[2]);
+    /// ```
+    pub fn intersect(&self, other: &Self) -> Self {
+        Self::new_with_mask(self.mask() & other.mask())
+    }
 }
 
 impl From<N> for Reg {
@@ -179,6 +215,28 @@ mod tests {
         assert_eq!(v[[0, 7]], 0b10000001);
     }
 
+    #[test]
+    fn union_and_intersect() {
+        let v = Reg::new(8);
+        let e = Reg::from(v[|i| i & 1 == 0]);
+        let h = Reg::from(v[[4, 5, 6, 7]]);
+
+        let u = e.union(&h);
+        assert_eq!(u[..], 0b11110101);
+        assert_eq!(u[0], 0b00000001);
+        assert_eq!(u[2], 0b00010000);
+        assert_eq!(u[[0, 5]], 0b10000001);
+        assert_eq!(u[..], h.union(&e)[..]);
+
+        let i = e.intersect(&h);
+        assert_eq!(i[..], 0b01010000);
+        assert_eq!(i[0], 0b00010000);
+        assert_eq!(i[1], 0b01000000);
+
+        assert_eq!(e.intersect(&Reg::from(v[|i| i & 1 != 0])).1, vec![]);
+        assert_eq!(e.union(&Reg::default())[..], e[..]);
+    }
+
     #[test]
     fn virtual_regs() {
         use crate::prelude::*;
@@ -29,14 +29,13 @@ where
         .unwrap()
 }
 
-fn set_num_threads(num_threads: usize) {
-    *GLOBAL_POOL.write().unwrap() = Some((
-        num_threads,
-        ThreadPoolBuilder::new()
-            .num_threads(num_threads)
-            .build()
-            .unwrap(),
-    ));
+pub fn try_set_num_threads(
+    num_threads: usize,
+    builder: ThreadPoolBuilder,
+) -> Result<(), ThreadPoolBuildError> {
+    let pool = builder.num_threads(num_threads).build()?;
+    *GLOBAL_POOL.write().unwrap() = Some((num_threads, pool));
+    Ok(())
 }
 
 pub fn global_install<OP, R>(num_threads: usize, op: OP) -> R
@@ -46,7 +45,12 @@ where
 {
     match get_current_num_threads() {
         Some(th) if th == num_threads => {}
-        _ => set_num_threads(num_threads),
+        _ => {
+            // If pool could not be created, parallel iterators run in rayon's global pool
+            if try_set_num_threads(num_threads, ThreadPoolBuilder::new()).is_err() {
+                return op();
+            }
+        }
     };
     global_install_unchecked(op)
 }